@@ -8,7 +8,7 @@ trait BarrierLike: Clone + Send {
 
 impl BarrierLike for rendezvous::Rendezvous {
     fn wait(self) {
-        rendezvous::Rendezvous::wait(self);
+        let _ = rendezvous::Rendezvous::wait(self);
     }
 }
 
@@ -43,7 +43,7 @@ fn bench_rendezvous(depth: usize) -> Duration {
     let start = Instant::now();
     let b = rendezvous::Rendezvous::new();
     recurse_barrier(N_CHILD, depth, b.clone());
-    b.wait();
+    let _ = b.wait();
     start.elapsed()
 }
 fn bench_adaptive(depth: usize) -> Duration {
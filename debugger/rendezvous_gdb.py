@@ -0,0 +1,73 @@
+# GDB pretty-printers for the rendezvous crate.
+#
+# This script is embedded in binaries linking rendezvous through the
+# `debugger_visualizer` attribute and is picked up automatically provided
+# auto-loading is allowed (see `info auto-load python-scripts`). It can also
+# be loaded by hand with `source debugger/rendezvous_gdb.py`.
+
+import gdb
+
+# POISONED flag of rendezvous_core::Counters.
+_POISONED = 1 << 2
+
+
+def _unwrap_int(val):
+    # Descend through the single-field wrappers (CachePadded, AtomicU32,
+    # UnsafeCell) down to the integer they hold.
+    while val.type.strip_typedefs().code == gdb.TYPE_CODE_STRUCT:
+        val = val[val.type.strip_typedefs().fields()[0]]
+    return int(val)
+
+
+def _name(inner):
+    # Box<str>, empty if anonymous.
+    name = inner["name"]
+    length = int(name["length"])
+    if length == 0:
+        return None
+    return name["data_ptr"].string(encoding="utf-8", length=length)
+
+
+class RendezvousPrinter:
+    def __init__(self, val):
+        self.val = val
+
+    def _inner(self):
+        return self.val["ptr"]["pointer"].dereference()
+
+    def _counts(self):
+        counters = self._inner()["counters"]
+        live = _unwrap_int(counters["live"])
+        alloc_dep = _unwrap_int(counters["alloc_dep"])
+        blocked = _unwrap_int(counters["parked"])
+        poisoned = _unwrap_int(counters["flags"]) & _POISONED != 0
+        return live, alloc_dep - live, blocked, poisoned
+
+    def to_string(self):
+        live, waiting, blocked, poisoned = self._counts()
+        name = _name(self._inner())
+        prefix = "rendezvous {}".format(name) if name is not None else "rendezvous"
+        summary = "{}: {} live, {} waiting ({} blocked)".format(
+            prefix, live, waiting, blocked
+        )
+        return summary + ", poisoned" if poisoned else summary
+
+    def children(self):
+        live, waiting, blocked, poisoned = self._counts()
+        name = _name(self._inner())
+        if name is not None:
+            yield "name", name
+        yield "live", live
+        yield "waiting", waiting
+        yield "blocked", blocked
+        yield "poisoned", poisoned
+
+
+def _lookup(val):
+    if val.type.strip_typedefs().tag == "rendezvous::Rendezvous":
+        return RendezvousPrinter(val)
+    return None
+
+
+_objfile = gdb.current_objfile()
+(_objfile if _objfile is not None else gdb).pretty_printers.append(_lookup)
@@ -0,0 +1,58 @@
+# LLDB summary provider for the rendezvous crate.
+#
+# LLDB cannot load scripts embedded in binaries, load this one by hand with:
+#
+#     command script import debugger/rendezvous_lldb.py
+
+import lldb
+
+# POISONED flag of rendezvous_core::Counters.
+_POISONED = 1 << 2
+
+
+def _unwrap_int(valobj):
+    # Descend through the single-field wrappers (CachePadded, AtomicU32,
+    # UnsafeCell) down to the integer they hold.
+    while valobj.GetType().IsAggregateType():
+        valobj = valobj.GetChildAtIndex(0)
+    return valobj.GetValueAsUnsigned()
+
+
+def _name(inner):
+    # Box<str>, empty if anonymous.
+    name = inner.GetChildMemberWithName("name")
+    length = name.GetChildMemberWithName("length").GetValueAsUnsigned()
+    if length == 0:
+        return None
+    address = name.GetChildMemberWithName("data_ptr").GetValueAsUnsigned()
+    error = lldb.SBError()
+    data = inner.GetProcess().ReadMemory(address, length, error)
+    if not error.Success():
+        return None
+    return data.decode("utf-8", "replace")
+
+
+def rendezvous_summary(valobj, _dict):
+    inner = (
+        valobj.GetNonSyntheticValue()
+        .GetChildMemberWithName("ptr")
+        .GetChildMemberWithName("pointer")
+        .Dereference()
+    )
+    counters = inner.GetChildMemberWithName("counters")
+    live = _unwrap_int(counters.GetChildMemberWithName("live"))
+    alloc_dep = _unwrap_int(counters.GetChildMemberWithName("alloc_dep"))
+    blocked = _unwrap_int(counters.GetChildMemberWithName("parked"))
+    flags = _unwrap_int(counters.GetChildMemberWithName("flags"))
+    name = _name(inner)
+    prefix = "rendezvous {}".format(name) if name is not None else "rendezvous"
+    summary = "{}: {} live, {} waiting ({} blocked)".format(
+        prefix, live, alloc_dep - live, blocked
+    )
+    return summary + ", poisoned" if flags & _POISONED else summary
+
+
+def __lldb_init_module(debugger, _dict):
+    debugger.HandleCommand(
+        'type summary add -F rendezvous_lldb.rendezvous_summary "rendezvous::Rendezvous"'
+    )
@@ -0,0 +1,260 @@
+//! Allocation-free async waiting, for no_std executors such as embassy.
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::{AtomicBool, AtomicU8};
+use core::{
+    cell::UnsafeCell,
+    future::Future,
+    hint,
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::{AtomicBool, AtomicU8};
+
+use crate::Counters;
+
+/// A fixed-capacity set of wakers, for the futures waiting on some
+/// [`Counters`].
+///
+/// Each pending [`WaitFuture`] occupies one of the `N` slots. When all of
+/// them are taken, additional futures still work but wake themselves up on
+/// every poll, busy-polling through the executor until a slot frees up.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous_core::{future::WakerSet, Counters};
+///
+/// static COUNTERS: Counters = Counters::new();
+/// static WAKERS: WakerSet<2> = WakerSet::new();
+///
+/// // In an interrupt handler, or another task.
+/// fn transfer_done() {
+///     if COUNTERS.arrive() == 0 {
+///         WAKERS.wake_all();
+///     }
+/// }
+///
+/// async fn main_task() {
+///     COUNTERS.wait_async(&WAKERS).await;
+/// }
+/// ```
+pub struct WakerSet<const N: usize> {
+    slots: [Slot; N],
+}
+
+struct Slot {
+    claimed: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl<const N: usize> WakerSet<N> {
+    /// Creates an empty set of wakers.
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::NEW; N],
+        }
+    }
+
+    /// Wakes up all the futures waiting on this set.
+    pub fn wake_all(&self) {
+        for slot in &self.slots {
+            slot.waker.wake();
+        }
+    }
+
+    /// Registers `waker` to be woken up by [`WakerSet::wake_all`], in
+    /// `slot` or in a newly claimed slot if `slot` is `None`, and returns the
+    /// slot used.
+    ///
+    /// This is for futures other than [`WaitFuture`] waiting on the set,
+    /// which pass the slot returned by their previous poll and
+    /// [unregister](WakerSet::unregister) it once done. When all the slots
+    /// are taken, this returns `None` and wakes up `waker` right away.
+    pub fn register(&self, slot: Option<usize>, waker: &Waker) -> Option<usize> {
+        let slot = slot.or_else(|| self.claim());
+        match slot {
+            Some(i) => self.slots[i].waker.register(waker),
+            // No slot available, poll again later.
+            None => waker.wake_by_ref(),
+        }
+        slot
+    }
+
+    /// Gives up a slot returned by [`WakerSet::register`], for other futures
+    /// to claim.
+    pub fn unregister(&self, slot: usize) {
+        // Drop the waker we may have left.
+        drop(self.slots[slot].waker.take());
+        self.slots[slot].claimed.store(false, Ordering::Release);
+    }
+
+    fn claim(&self) -> Option<usize> {
+        self.slots.iter().position(|slot| {
+            slot.claimed
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        })
+    }
+}
+
+impl<const N: usize> Default for WakerSet<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Slot {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Self = Self {
+        claimed: AtomicBool::new(false),
+        waker: AtomicWaker::new(),
+    };
+}
+
+impl Counters {
+    /// Returns a future resolving once there is no live handle anymore,
+    /// registering its waker in `wakers`.
+    ///
+    /// This is the async counterpart of [`Counters::wait`], and must
+    /// similarly be used after [`Counters::arrive`]. Whoever arrives last
+    /// must then call [`WakerSet::wake_all`].
+    pub fn wait_async<'a, const N: usize>(&'a self, wakers: &'a WakerSet<N>) -> WaitFuture<'a, N> {
+        WaitFuture {
+            counters: self,
+            wakers,
+            slot: None,
+        }
+    }
+}
+
+/// Future returned by [`Counters::wait_async`].
+pub struct WaitFuture<'a, const N: usize> {
+    counters: &'a Counters,
+    wakers: &'a WakerSet<N>,
+    slot: Option<usize>,
+}
+
+impl<const N: usize> Future for WaitFuture<'_, N> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.counters.live() == 0 {
+            return Poll::Ready(());
+        }
+        this.slot = this.wakers.register(this.slot, cx.waker());
+        // The last handle may have arrived before we registered.
+        if this.counters.live() == 0 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<const N: usize> Drop for WaitFuture<'_, N> {
+    fn drop(&mut self) {
+        if let Some(i) = self.slot {
+            self.wakers.unregister(i);
+        }
+    }
+}
+
+/// A waker shared by a single registering future and any number of waking
+/// threads, without locks.
+struct AtomicWaker {
+    state: AtomicU8,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+/// Nobody is accessing the waker.
+const IDLE: u8 = 0;
+/// The registering future is writing the waker.
+const REGISTERING: u8 = 0b01;
+/// A waking thread is taking the waker.
+const WAKING: u8 = 0b10;
+
+impl AtomicWaker {
+    const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(IDLE),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers `waker` to be woken up by the next call to
+    /// [`AtomicWaker::wake`].
+    ///
+    /// Must not be called concurrently with itself, which is guaranteed by
+    /// slots being claimed by a single future.
+    fn register(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(IDLE, REGISTERING, Ordering::Acquire, Ordering::Acquire)
+            .unwrap_or_else(|state| state)
+        {
+            IDLE => {
+                // SAFETY: the REGISTERING state grants us exclusive access to
+                // the waker.
+                let slot = unsafe { &mut *self.waker.get() };
+                match slot {
+                    Some(old) if old.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                }
+                if self
+                    .state
+                    .compare_exchange(REGISTERING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    // A waking thread came in the meantime and left us the
+                    // job of waking up. The state is REGISTERING | WAKING so
+                    // we still have exclusive access.
+                    // SAFETY: see above.
+                    let waker = unsafe { &mut *self.waker.get() }.take();
+                    self.state.swap(IDLE, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            WAKING => {
+                // Currently being woken up, wake up again to be polled after
+                // that.
+                waker.wake_by_ref();
+                hint::spin_loop();
+            }
+            _ => unreachable!("concurrent registrations on one AtomicWaker"),
+        }
+    }
+
+    /// Takes the registered waker, if any.
+    fn take(&self) -> Option<Waker> {
+        match self.state.fetch_or(WAKING, Ordering::AcqRel) {
+            IDLE => {
+                // SAFETY: the WAKING state grants us exclusive access to the
+                // waker.
+                let waker = unsafe { &mut *self.waker.get() }.take();
+                self.state.fetch_and(!WAKING, Ordering::Release);
+                waker
+            }
+            // Either the registering future will see WAKING and wake itself
+            // up, or another thread is already taking the waker.
+            _ => None,
+        }
+    }
+
+    /// Wakes up the registered waker, if any.
+    fn wake(&self) {
+        if let Some(waker) = self.take() {
+            waker.wake();
+        }
+    }
+}
+
+// Safety: the waker is only accessed under the exclusive states of the
+// protocol, and Waker is Send.
+unsafe impl Sync for AtomicWaker {}
@@ -0,0 +1,886 @@
+#![no_std]
+#![warn(clippy::undocumented_unsafe_blocks)]
+
+//! The counters and wait protocol at the heart of the
+//! [`rendezvous`](https://docs.rs/rendezvous) crate.
+//!
+//! This crate has no dependency and does not allocate, so that other
+//! synchronization crates and embedded projects can build on the protocol
+//! without pulling the higher-level types of `rendezvous`. Blocking is left
+//! to the embedder, through the [`Futex`] trait.
+//!
+//! # Protocol
+//!
+//! A rendezvous is a shared allocation holding [`Counters`], and each handle
+//! on it owns one unit of both of its counters:
+//!
+//! - `live` counts the handles which have not arrived yet, i.e. which have
+//!   been neither dropped nor waited on,
+//! - `alloc_dep` counts the handles which still need the allocation to be
+//!   valid.
+//!
+//! Cloning a handle [registers](Counters::register) a new unit of both, while
+//! handles which only observe the rendezvous [retain](Counters::retain) a unit
+//! of `alloc_dep` only.
+//! Dropping a handle [arrives](Counters::arrive), waking up the waiters if it
+//! was the last live one, then [releases](Counters::release) its allocation
+//! dependency, freeing the allocation if it was the last one. Waiting is the
+//! same as dropping, except that the handle [waits](Counters::wait) for
+//! `live` to reach zero between arriving and releasing. A waiting handle
+//! which may give up, e.g. on timeout, arrives
+//! [cancellably](Counters::arrive_cancellable), and can then
+//! [cancel](Counters::unarrive) its arrival as long as `live` has not
+//! reached zero. The waiting handles can [elect](Counters::elect) a leader
+//! among themselves once it has.
+//! A live handle can also [wait for the others](Counters::wait_others)
+//! without arriving.
+//!
+//! Since `live <= alloc_dep` at all times, a handle can always access the
+//! counters until it releases its allocation dependency.
+//!
+//! # 64-bit counters
+//!
+//! The counters are [`Count`]s, i.e. `u32`, which caps the number of
+//! simultaneous handles to 2³² - 1. With the `u64-counters` feature, they
+//! are `u64` instead, for programs registering handles at rates where this
+//! is a concern. This requires 64-bit atomics, and since futexes are 32-bit,
+//! the waits then block on a separate count of the wake-ups instead of on
+//! `live`, see [`Counters::futex_word`].
+//!
+//! # Portable atomics
+//!
+//! Some targets, such as `thumbv6m-none-eabi` or RISC-V cores without the
+//! A extension, load and store 32-bit atomics but cannot update them
+//! atomically. With the `portable-atomic` feature, the counters are updated
+//! through [portable-atomic](https://docs.rs/portable-atomic) instead, which
+//! emulates the missing operations. On these targets, it must itself be told
+//! how, with either its `critical-section` feature or, on single-core chips,
+//! its `unsafe-assume-single-core` cfg. The futex word stays a core
+//! [`AtomicU32`], so that [`Futex`] implementations are the same with or
+//! without the feature.
+//!
+//! # Async
+//!
+//! With the `async` feature, [`Counters::wait_async`] waits for the live
+//! handles without blocking, registering wakers in a fixed-capacity
+//! [`WakerSet`](future::WakerSet). It neither allocates nor locks, and thus
+//! works with no_std executors such as embassy.
+//!
+//! # ThreadSanitizer
+//!
+//! Building with `--cfg rendezvous_tsan` (e.g. through `RUSTFLAGS`) annotates
+//! the happens-before edges of the protocol for ThreadSanitizer, so that
+//! programs built on it can run under TSan without suppressions. The
+//! annotations rely on the TSan runtime, and linking fails without
+//! `-Zsanitizer=thread`.
+//!
+//! # Loom
+//!
+//! Building with `--cfg loom` (e.g. through `RUSTFLAGS`) switches the
+//! atomics of the counters to those of [loom](https://docs.rs/loom), so that
+//! code built on the protocol can be model-checked, blocking through
+//! `LoomFutex`. The constructors of the counters are not `const` then. The
+//! protocol itself is checked by the loom tests of this crate:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test -p rendezvous-core --test loom --release
+//! ```
+//!
+//! # Kani
+//!
+//! The allocation dependencies are also checked with
+//! [Kani](https://model-checking.github.io/kani/), to never be used after
+//! the allocation is freed nor to free it twice, whatever the interleaving
+//! of clones, drops and waits:
+//!
+//! ```text
+//! cargo kani -p rendezvous-core
+//! ```
+
+use core::{fmt::Debug, ops::Deref, time::Duration};
+
+#[cfg(loom)]
+pub use sync::LoomFutex;
+use sync::{fence, futex_atomic, rmw, AtomicCount, AtomicU32, Ordering};
+
+#[cfg(feature = "async")]
+pub mod future;
+#[cfg(kani)]
+mod proofs;
+#[macro_use]
+mod sync;
+mod tsan;
+
+/// The number of handles on a rendezvous, `u64` with the `u64-counters`
+/// feature and `u32` otherwise.
+#[cfg(not(feature = "u64-counters"))]
+pub type Count = u32;
+/// The number of handles on a rendezvous, `u64` with the `u64-counters`
+/// feature and `u32` otherwise.
+#[cfg(feature = "u64-counters")]
+pub type Count = u64;
+
+/// The message of the panics when there would be too many handles.
+#[cfg(not(feature = "u64-counters"))]
+const TOO_MANY: &str = "There should not be more than 2^32 - 1 clones of one Rendezvous.";
+#[cfg(feature = "u64-counters")]
+const TOO_MANY: &str = "There should not be more than 2^64 - 1 clones of one Rendezvous.";
+
+/// Blocking primitives on an [`AtomicU32`], aka. futex.
+pub trait Futex {
+    /// If the value of `atomic` is `value`, blocks until woken up.
+    ///
+    /// This function may also return spuriously, without a corresponding
+    /// wake operation.
+    fn wait(atomic: &AtomicU32, value: u32);
+
+    /// Wakes up all the threads blocked in [`Futex::wait`] on `atomic`.
+    ///
+    /// It must be okay for the pointer to dangle.
+    fn wake_all(atomic: *const AtomicU32);
+
+    /// Blocks like [`Futex::wait`], as a waiter of the `classes` set in a
+    /// non-zero bitset, which only the wakes of one of these classes need to
+    /// wake up.
+    ///
+    /// By default, this is [`Futex::wait`], woken up by all the wakes.
+    fn wait_class(atomic: &AtomicU32, value: u32, classes: u32) {
+        let _ = classes;
+        Self::wait(atomic, value);
+    }
+
+    /// Wakes up the threads blocked on `atomic` in [`Futex::wait`], or in
+    /// [`Futex::wait_class`] as a waiter of one of the `classes` set in a
+    /// non-zero bitset.
+    ///
+    /// It must be okay for the pointer to dangle. By default, this is
+    /// [`Futex::wake_all`], waking up the waiters of all the classes.
+    fn wake_class(atomic: *const AtomicU32, classes: u32) {
+        let _ = classes;
+        Self::wake_all(atomic);
+    }
+}
+
+/// A [`Futex`] which can also block with a timeout.
+pub trait TimedFutex: Futex {
+    /// If the value of `atomic` is `value`, blocks until woken up or until
+    /// `timeout` has elapsed.
+    ///
+    /// This function may also return spuriously, before the timeout and
+    /// without a corresponding wake operation.
+    fn wait_timeout(atomic: &AtomicU32, value: u32, timeout: Duration);
+}
+
+/// The counters shared by all the handles on a rendezvous. See the
+/// [crate] documentation for the protocol they implement.
+///
+/// # Remarks
+///
+/// - There cannot be more than 2³² - 1 simultaneous handles on a single
+///   rendezvous, or 2⁶⁴ - 1 with the `u64-counters` feature.
+#[repr(C)]
+pub struct Counters {
+    live: CachePadded<AtomicCount>,
+    // Only modified when a handle goes away, they share the line following
+    // live.
+    alloc_dep: AtomicCount,
+    flags: rmw::AtomicU32,
+    panicked: rmw::AtomicU32,
+    // After the others, so that the offsets read by the debugger visualizers
+    // of `rendezvous` stay the same.
+    parked: rmw::AtomicU32,
+    // The arrivals which may be cancelled, and those of them which have been
+    // either cancelled or completed since, see Counters::wait_others.
+    cancellable: rmw::AtomicU32,
+    settled: rmw::AtomicU32,
+    // Incremented by each wake-up, the futex word of the waits since live
+    // does not fit in one.
+    #[cfg(feature = "u64-counters")]
+    wakes: rmw::AtomicU32,
+}
+
+// Flags, POISONED being also read by the debugger visualizers of
+// `rendezvous`.
+/// Set once a leader has been elected.
+const LEADER: u32 = 1;
+/// Set at creation if handles dropped while panicking poison the rendezvous,
+/// i.e. with [`PanicMode::Poison`] and [`PanicMode::Propagate`].
+const POISONING: u32 = 1 << 1;
+/// Set once the rendezvous is poisoned.
+const POISONED: u32 = 1 << 2;
+/// Set at creation with [`PanicMode::Propagate`].
+const PROPAGATING: u32 = 1 << 3;
+/// Set once a handle waited with [`Counters::wait_others`].
+const AWAITING_OTHERS: u32 = 1 << 4;
+
+/// The value of live while the last live handle acts before arriving, see
+/// [`Counters::arrive_if_not_last`]. It is never a number of live handles,
+/// since there are fewer of them than dependencies on the allocation.
+const FINISHING: Count = Count::MAX;
+
+// Classes of waiters, see Futex::wait_class.
+/// Waiting until there is no live handle anymore.
+const COMPLETION: u32 = 1;
+/// Waiting until the calling handle is the last live one.
+const LAST_LIVE: u32 = 1 << 1;
+
+/// What happens to a rendezvous when one of its handles is dropped while
+/// panicking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PanicMode {
+    /// The handle arrives as if dropped normally.
+    #[default]
+    Ignore,
+    /// The rendezvous is poisoned, and its waits report it.
+    Poison,
+    /// The rendezvous is poisoned, and its waits panic in turn.
+    Propagate,
+}
+
+impl Counters {
+    const_fn! {
+        /// Creates the counters of a new rendezvous, with a single handle.
+        pub const fn new() -> Self {
+            Self::with_flags(0)
+        }
+    }
+
+    const_fn! {
+        /// Creates the counters of a new rendezvous, with a single handle,
+        /// which handles panics according to `mode`.
+        pub const fn with_panic_mode(mode: PanicMode) -> Self {
+            Self::with_flags(match mode {
+                PanicMode::Ignore => 0,
+                PanicMode::Poison => POISONING,
+                PanicMode::Propagate => POISONING | PROPAGATING,
+            })
+        }
+    }
+
+    const_fn! {
+        /// Returns the same counters, but with `n` handles instead of one.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is zero.
+        pub const fn with_count(self, n: Count) -> Self {
+            assert!(n > 0, "A rendezvous should have at least one handle.");
+            Self {
+                live: CachePadded(AtomicCount::new(n)),
+                alloc_dep: AtomicCount::new(n),
+                ..self
+            }
+        }
+    }
+
+    const_fn! {
+        const fn with_flags(flags: u32) -> Self {
+            Self {
+                live: CachePadded(AtomicCount::new(1)),
+                alloc_dep: AtomicCount::new(1),
+                flags: rmw::AtomicU32::new(flags),
+                panicked: rmw::AtomicU32::new(0),
+                parked: rmw::AtomicU32::new(0),
+                cancellable: rmw::AtomicU32::new(0),
+                settled: rmw::AtomicU32::new(0),
+                #[cfg(feature = "u64-counters")]
+                wakes: rmw::AtomicU32::new(0),
+            }
+        }
+    }
+
+    /// Resets the counters to those of a new rendezvous with the same
+    /// [`PanicMode`], with a single handle.
+    ///
+    /// The caller is expected to hold the only handle, as the others would
+    /// be forgotten. Others may still read the counters in the meantime,
+    /// e.g. for diagnostics.
+    pub fn reset(&self) {
+        self.live.store(1, Ordering::Relaxed);
+        self.alloc_dep.store(1, Ordering::Relaxed);
+        self.flags
+            .fetch_and(POISONING | PROPAGATING, Ordering::Relaxed);
+        self.panicked.store(0, Ordering::Relaxed);
+        // Without other handles, nobody is parked, and all the cancellable
+        // arrivals are settled.
+    }
+
+    /// Registers a new handle.
+    ///
+    /// # Panics
+    ///
+    /// If there are already 2³² - 1 handles.
+    pub fn register(&self) {
+        self.register_n(1);
+    }
+
+    /// Registers `n` new handles at once.
+    ///
+    /// # Panics
+    ///
+    /// If there would be more than 2³² - 1 handles.
+    pub fn register_n(&self, n: Count) {
+        if !self.try_register_n(n) {
+            panic!("{}", TOO_MANY);
+        }
+    }
+
+    /// Registers `n` new handles at once, unless there would be more than
+    /// 2³² - 1 handles. Returns whether they were registered.
+    pub fn try_register_n(&self, n: Count) -> bool {
+        if self
+            .alloc_dep
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |d| d.checked_add(n))
+            .is_err()
+        {
+            return false;
+        }
+        // This one cannot overflow because live < alloc_dep
+        // at all times
+        self.live.fetch_add(n, Ordering::AcqRel);
+        true
+    }
+
+    /// Registers a new handle from a dependency on the allocation, unless
+    /// there is no live handle anymore, or the last one is about to arrive,
+    /// see [`Counters::unarrive`]. Returns whether the handle was registered.
+    ///
+    /// The caller must own a dependency on the allocation, e.g. from
+    /// [`Counters::retain`].
+    ///
+    /// # Panics
+    ///
+    /// If there are already 2³² - 1 handles.
+    pub fn try_register(&self) -> bool {
+        self.retain();
+        self.register_retained()
+    }
+
+    /// Registers a new handle from a dependency on the allocation the caller
+    /// just [retained](Counters::retain), unless there is no live handle
+    /// anymore or the last one is about to arrive, in which case the
+    /// dependency is released. Returns whether the handle was registered.
+    ///
+    /// The caller must own another dependency on the allocation, like for
+    /// [`Counters::try_register`] which is this after retaining.
+    pub fn register_retained(&self) -> bool {
+        if self.unarrive() {
+            return true;
+        }
+        // The caller owns another dependency, so this cannot be the last one.
+        let last = self.release();
+        debug_assert!(!last);
+        false
+    }
+
+    /// Registers a new dependency on the allocation, without a live handle.
+    ///
+    /// This is for handles which do not take part in the rendezvous but
+    /// still need the allocation, e.g. to wait for its completion. Such
+    /// dependencies are given up with [`Counters::release`] only.
+    ///
+    /// # Panics
+    ///
+    /// If there are already 2³² - 1 handles.
+    pub fn retain(&self) {
+        if !self.try_retain() {
+            panic!("{}", TOO_MANY);
+        }
+    }
+
+    /// Registers a new dependency on the allocation, like
+    /// [`Counters::retain`], unless there are already 2³² - 1 handles.
+    /// Returns whether it was registered.
+    pub fn try_retain(&self) -> bool {
+        self.alloc_dep
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |n| n.checked_add(1))
+            .is_ok()
+    }
+
+    /// Marks a handle as arrived, and returns the new number of live
+    /// handles.
+    ///
+    /// When this returns zero, the caller was the last live handle and is
+    /// responsible for waking up the waiters, e.g. with
+    /// [`Counters::wake_all`].
+    pub fn arrive(&self) -> Count {
+        self.arrive_n(1)
+    }
+
+    /// Marks `n` handles as arrived at once, see [`Counters::arrive`].
+    pub fn arrive_n(&self, n: Count) -> Count {
+        tsan::release(&self.live);
+        self.live.fetch_sub(n, Ordering::AcqRel) - n
+    }
+
+    /// Marks a handle as arrived, like [`Counters::arrive`], in order to wait
+    /// with [`Counters::wait_deadline`], which may cancel the arrival.
+    ///
+    /// Until the wait returns, the handles
+    /// [waiting for the others](Counters::wait_others) do not count the
+    /// arrival, since it may be cancelled.
+    pub fn arrive_cancellable(&self) -> Count {
+        self.cancellable.fetch_add(1, Ordering::Relaxed);
+        self.arrive()
+    }
+
+    /// Marks a handle as arrived if it is the last live one, and returns
+    /// whether it was.
+    ///
+    /// When this returns `true`, the caller is responsible for waking up the
+    /// waiters, like when [`Counters::arrive`] returns zero. Otherwise, the
+    /// handle is still live, and never counted as arrived in the meantime.
+    pub fn arrive_if_last(&self) -> bool {
+        tsan::release(&self.live);
+        self.live
+            .compare_exchange(1, 0, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Marks a handle as arrived unless it is the last live one, and returns
+    /// the new number of live handles, which is thus never zero.
+    ///
+    /// When this returns `None`, the handle is still live, and no handle can
+    /// be [registered](Counters::register_retained) from a dependency on the
+    /// allocation anymore, e.g. by upgrading a weak one. Since registering
+    /// any other way requires a live handle, it then remains the last live
+    /// handle, and can act before anyone is released. It must then arrive
+    /// with [`Counters::finish`].
+    pub fn arrive_if_not_last(&self) -> Option<Count> {
+        tsan::release(&self.live);
+        let l = match self
+            .live
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |l| {
+                Some(if l > 1 { l - 1 } else { FINISHING })
+            }) {
+            Ok(l) | Err(l) => l,
+        };
+        (l > 1).then(|| l - 1)
+    }
+
+    /// Marks the last live handle as arrived once it is done acting, after
+    /// [`Counters::arrive_if_not_last`] returned `None`.
+    ///
+    /// The caller is then responsible for waking up the waiters, like when
+    /// [`Counters::arrive`] returns zero.
+    pub fn finish(&self) {
+        tsan::release(&self.live);
+        let l = self.live.swap(0, Ordering::AcqRel);
+        debug_assert_eq!(l, FINISHING);
+    }
+
+    /// Blocks until there is no live handle anymore, `live` being the last
+    /// value returned by [`Counters::arrive`].
+    pub fn wait<F: Futex>(&self, live: Count) {
+        if live > 0 {
+            let _parked = self.park();
+            loop {
+                let word = self.futex_word();
+                if word.live == 0 {
+                    break;
+                }
+                // There are still some live barriers
+                F::wait_class(word.atomic, word.value, COMPLETION);
+            }
+        }
+        tsan::acquire(&self.live);
+    }
+
+    /// Blocks until the calling handle, which stays live, is the last live
+    /// one.
+    ///
+    /// The handles arriving while others wait this way must then wake them
+    /// up, see [`Counters::awaits_others`]. The
+    /// [cancellable arrivals](Counters::arrive_cancellable) are only counted
+    /// once their wait returns without cancelling them. Two handles waiting
+    /// this way at the same time wait for each other forever.
+    pub fn wait_others<F: Futex>(&self) {
+        self.flags.fetch_or(AWAITING_OTHERS, Ordering::Relaxed);
+        // Pairs with the fence of awaits_others: either the arriving handle
+        // sees the flag, or we see its arrival.
+        fence(Ordering::SeqCst);
+        let mut parked = None;
+        loop {
+            // Read before live, so that a cancellable arrival seen by the
+            // latter is either counted by cancellable, read after it, or
+            // settled, and then cancelled before live was read.
+            let settled = self.settled.load(Ordering::Acquire);
+            let word = self.futex_word();
+            if word.live > 1 {
+                parked.get_or_insert_with(|| self.park());
+                F::wait_class(word.atomic, word.value, LAST_LIVE);
+            } else if self.cancellable.load(Ordering::Acquire) != settled {
+                // Some of the arrivals may still be cancelled.
+                parked.get_or_insert_with(|| self.park());
+                F::wait_class(futex_atomic(&self.settled), settled, LAST_LIVE);
+            } else {
+                break;
+            }
+        }
+        // Spares the wake-ups to the arrivals to come.
+        self.flags.fetch_and(!AWAITING_OTHERS, Ordering::Relaxed);
+        tsan::acquire(&self.live);
+    }
+
+    /// Returns whether a handle may be blocked in [`Counters::wait_others`].
+    ///
+    /// Handles which [arrive](Counters::arrive) leaving a single live handle
+    /// call it, and [wake up](Counters::wake_others) the waiters if it
+    /// returns `true`.
+    pub fn awaits_others(&self) -> bool {
+        fence(Ordering::SeqCst);
+        self.flags.load(Ordering::Relaxed) & AWAITING_OTHERS != 0
+    }
+
+    /// Blocks until there is no live handle anymore or until `deadline`,
+    /// `live` being the value returned by [`Counters::arrive_cancellable`].
+    ///
+    /// `now` reads the clock against which `deadline` is measured. Returns
+    /// `true` if there is no live handle anymore, and `false` if the deadline
+    /// was reached first, in which case the arrival has been
+    /// [cancelled](Counters::unarrive) and the caller is live again. The
+    /// deadline is not enforced while the last live handle acts before
+    /// arriving, see [`Counters::arrive_if_not_last`].
+    pub fn wait_deadline<F: TimedFutex>(
+        &self,
+        live: Count,
+        deadline: Duration,
+        now: impl FnMut() -> Duration,
+    ) -> bool {
+        let complete = match self.wait_before::<F>(live, deadline, now) {
+            Ok(()) => true,
+            Err(_) if self.unarrive() => false,
+            // Either the last live handle arrived after the deadline, or it
+            // acts before arriving and the rendezvous is as good as complete.
+            Err(l) => {
+                self.wait::<F>(l);
+                true
+            }
+        };
+        self.settled.fetch_add(1, Ordering::Release);
+        if self.awaits_others() {
+            F::wake_class(futex_atomic(&self.settled), LAST_LIVE);
+        }
+        complete
+    }
+
+    /// Blocks until there is no live handle anymore or until `deadline`,
+    /// like [`Counters::wait_deadline`], but without cancelling the arrival.
+    ///
+    /// Returns `Err` with the last number of live handles read if the
+    /// deadline was reached first, which can be passed to a subsequent
+    /// [`Counters::wait`] to keep waiting.
+    pub fn wait_before<F: TimedFutex>(
+        &self,
+        live: Count,
+        deadline: Duration,
+        mut now: impl FnMut() -> Duration,
+    ) -> Result<(), Count> {
+        if live > 0 {
+            let _parked = self.park();
+            loop {
+                let word = self.futex_word();
+                if word.live == 0 {
+                    break;
+                }
+                let remaining = deadline.saturating_sub(now());
+                if remaining.is_zero() {
+                    return Err(word.live);
+                }
+                F::wait_timeout(word.atomic, word.value, remaining);
+            }
+        }
+        tsan::acquire(&self.live);
+        Ok(())
+    }
+
+    /// Records that the calling thread blocks on the counters until the
+    /// returned guard is dropped, see [`Counters::parked`].
+    ///
+    /// The waits of the counters do it on their own, this is for the
+    /// callers blocking on [`Counters::futex_word`] directly.
+    pub fn park(&self) -> Parked<'_> {
+        self.parked.fetch_add(1, Ordering::Relaxed);
+        Parked { counters: self }
+    }
+
+    /// Cancels the arrival of a handle, making it live again, unless there is
+    /// no live handle anymore. Returns whether the arrival was cancelled.
+    ///
+    /// Once there is no live handle, the waiters may have been released
+    /// already, so the rendezvous cannot be joined again. Neither can it
+    /// while the last live handle acts before arriving, see
+    /// [`Counters::arrive_if_not_last`].
+    pub fn unarrive(&self) -> bool {
+        // This one cannot overflow because live < alloc_dep
+        // at all times
+        self.live
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |l| {
+                (l > 0 && l != FINISHING).then_some(l + 1)
+            })
+            .is_ok()
+    }
+
+    /// Wakes up all the handles blocked in [`Counters::wait`],
+    /// [`Counters::wait_deadline`], [`Counters::wait_before`] or
+    /// [`Counters::wait_others`].
+    pub fn wake_all<F: Futex>(&self) {
+        #[cfg(feature = "u64-counters")]
+        self.wakes.fetch_add(1, Ordering::Release);
+        F::wake_all(self.word_atomic());
+    }
+
+    /// Wakes up the handle blocked in [`Counters::wait_others`], if any,
+    /// but not those waiting for all the handles.
+    pub fn wake_others<F: Futex>(&self) {
+        #[cfg(feature = "u64-counters")]
+        self.wakes.fetch_add(1, Ordering::Release);
+        F::wake_class(self.word_atomic(), LAST_LIVE);
+    }
+
+    /// Releases the allocation dependency of a handle, and returns whether
+    /// it was the last one.
+    ///
+    /// When this returns `false`, the allocation holding the counters may be
+    /// freed by another handle at any time, and the caller must not access
+    /// it anymore. When this returns `true`, the caller is responsible for
+    /// freeing it.
+    pub fn release(&self) -> bool {
+        self.release_n(1)
+    }
+
+    /// Releases the allocation dependencies of `n` handles at once, see
+    /// [`Counters::release`].
+    pub fn release_n(&self, n: Count) -> bool {
+        tsan::release(&self.alloc_dep);
+        let last = self.alloc_dep.fetch_sub(n, Ordering::AcqRel) == n;
+        if last {
+            tsan::acquire(&self.alloc_dep);
+        }
+        last
+    }
+
+    /// Elects the leader of a completed rendezvous: returns `true` for the
+    /// first caller only.
+    ///
+    /// Waiting handles call it once their wait completed, before releasing
+    /// their allocation dependency, so that exactly one of them is the
+    /// leader.
+    pub fn elect(&self) -> bool {
+        self.flags.fetch_or(LEADER, Ordering::AcqRel) & LEADER == 0
+    }
+
+    /// Records that a handle is dropped while panicking: counts it and
+    /// poisons the rendezvous unless its [`PanicMode`] is
+    /// [`PanicMode::Ignore`].
+    ///
+    /// This must be called before the handle [arrives](Counters::arrive), so
+    /// that the waiters see it once they are released.
+    pub fn record_panic(&self) {
+        self.panicked.fetch_add(1, Ordering::AcqRel);
+        if self.flags.load(Ordering::Relaxed) & POISONING != 0 {
+            self.flags.fetch_or(POISONED, Ordering::AcqRel);
+        }
+    }
+
+    /// Returns the [`PanicMode`] the rendezvous was created with.
+    pub fn panic_mode(&self) -> PanicMode {
+        let flags = self.flags.load(Ordering::Relaxed);
+        if flags & PROPAGATING != 0 {
+            PanicMode::Propagate
+        } else if flags & POISONING != 0 {
+            PanicMode::Poison
+        } else {
+            PanicMode::Ignore
+        }
+    }
+
+    /// Returns whether the rendezvous has been poisoned by a
+    /// [panic](Counters::record_panic).
+    pub fn is_poisoned(&self) -> bool {
+        self.flags.load(Ordering::Acquire) & POISONED != 0
+    }
+
+    /// Returns the number of handles dropped while
+    /// [panicking](Counters::record_panic).
+    pub fn panicked(&self) -> u32 {
+        self.panicked.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of live handles.
+    pub fn live(&self) -> Count {
+        match self.live.load(Ordering::Acquire) {
+            FINISHING => 1,
+            l => l,
+        }
+    }
+
+    /// Returns the number of live handles like [`Counters::live`], but
+    /// through a read-modify-write, so that the arrivals which do not see
+    /// its result see everything which happened before the call.
+    ///
+    /// This lets observers check for arrivals after registering somewhere
+    /// the arrivals look, without a fence on the side of the arrivals.
+    pub fn live_synchronized(&self) -> Count {
+        match self.live.fetch_add(0, Ordering::AcqRel) {
+            FINISHING => 1,
+            l => l,
+        }
+    }
+
+    /// Returns the atomic which [`Counters::wait`] blocks on, e.g. to wait
+    /// on several rendezvous at once with a platform-specific API.
+    ///
+    /// It must only be read, and woken up. It is `live` itself, unless with
+    /// the `u64-counters` feature, see [`Counters::futex_word`] to block on
+    /// it.
+    pub fn live_ptr(&self) -> *const AtomicU32 {
+        self.word_atomic()
+    }
+
+    /// Returns the atomic the waits block on, with the value to block on it
+    /// and the number of live handles, read in this order so that blocking
+    /// until the atomic changes from that value, e.g. with [`Futex::wait`],
+    /// returns once the number of live handles changes.
+    ///
+    /// This is how the waits of the counters block, for the callers using a
+    /// platform-specific API instead, e.g. to wait on several rendezvous at
+    /// once.
+    pub fn futex_word(&self) -> FutexWord<'_> {
+        #[cfg(not(feature = "u64-counters"))]
+        let word = {
+            // The raw value, which blocking compares with the atomic.
+            let value = self.live.load(Ordering::Acquire);
+            FutexWord {
+                atomic: self.word_atomic(),
+                value,
+                live: if value == FINISHING { 1 } else { value },
+            }
+        };
+        // Wake-ups come after the changes of live they report, so that if
+        // live changes after being read, so does value.
+        #[cfg(feature = "u64-counters")]
+        let word = FutexWord {
+            atomic: self.word_atomic(),
+            value: self.wakes.load(Ordering::Acquire),
+            live: self.live(),
+        };
+        word
+    }
+
+    fn word_atomic(&self) -> &AtomicU32 {
+        #[cfg(not(feature = "u64-counters"))]
+        let atomic = self.live.deref();
+        #[cfg(feature = "u64-counters")]
+        let atomic = &self.wakes;
+        futex_atomic(atomic)
+    }
+
+    /// Returns the number of handles which still need the allocation, live
+    /// or waiting.
+    pub fn alloc_dep(&self) -> Count {
+        self.alloc_dep.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of threads currently blocked in the waits of the
+    /// counters, or [parked](Counters::park) on them.
+    pub fn parked(&self) -> u32 {
+        self.parked.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Counters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Counters")
+            .field("live", &self.live())
+            .field("alloc_dep", &self.alloc_dep())
+            .field("poisoned", &self.is_poisoned())
+            .field("panicked", &self.panicked())
+            .field("parked", &self.parked())
+            .finish()
+    }
+}
+
+/// The atomic the waits of some [`Counters`] block on, returned by
+/// [`Counters::futex_word`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct FutexWord<'a> {
+    /// The atomic to block on.
+    pub atomic: &'a AtomicU32,
+    /// The value of `atomic` to block on.
+    pub value: u32,
+    /// The number of live handles, read after `value`.
+    pub live: Count,
+}
+
+/// A thread blocked on some [`Counters`], returned by [`Counters::park`].
+pub struct Parked<'a> {
+    counters: &'a Counters,
+}
+
+impl Drop for Parked<'_> {
+    fn drop(&mut self) {
+        self.counters.parked.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Debug for Parked<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Parked").finish_non_exhaustive()
+    }
+}
+
+/// Pads and aligns a value to the length of a cache line, so that the
+/// counters, which are modified by different handles at different times, do
+/// not contend for the same line.
+///
+/// Alignments are the same as crossbeam's.
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    any(
+        target_arch = "arm",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+    ),
+    repr(align(32))
+)]
+#[cfg_attr(target_arch = "s390x", repr(align(256)))]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "arm",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+        target_arch = "s390x",
+    )),
+    repr(align(64))
+)]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
@@ -0,0 +1,129 @@
+//! Kani proof harnesses of the protocol, built under `cfg(kani)`.
+//!
+//! Kani checks sequential code, so the interleavings are modelled: each
+//! handle runs the steps of its clone, drop or wait, which only touch the
+//! counters through their atomics, and Kani picks which handle runs its next
+//! step. The allocation is modelled by a flag set by the handle releasing
+//! the last allocation dependency, and checked before every access.
+
+use crate::Counters;
+
+/// The bounds of the models, kept small for the proofs to be tractable.
+const MAX_HANDLES: usize = 3;
+const MAX_STEPS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Live,
+    /// Arrived, about to release its allocation dependency.
+    Dropping,
+    /// Arrived, blocked until there is no live handle anymore.
+    Waiting,
+    Gone,
+}
+
+struct Model {
+    counters: Counters,
+    freed: bool,
+    handles: [Handle; MAX_HANDLES],
+    len: usize,
+    leaders: u32,
+}
+
+impl Model {
+    fn new(n: usize) -> Self {
+        let mut handles = [Handle::Gone; MAX_HANDLES];
+        handles[..n].fill(Handle::Live);
+        Self {
+            counters: Counters::new().with_count(n as crate::Count),
+            freed: false,
+            handles,
+            len: n,
+            leaders: 0,
+        }
+    }
+
+    fn counters(&self) -> &Counters {
+        assert!(!self.freed, "use after free");
+        &self.counters
+    }
+
+    fn release(&mut self) {
+        if self.counters().release() {
+            assert!(!self.freed, "double free");
+            self.freed = true;
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.handles[..self.len].iter().all(|&h| h == Handle::Gone)
+    }
+
+    /// Runs the next step of a handle picked by Kani.
+    fn step(&mut self) {
+        let i: usize = kani::any();
+        kani::assume(i < self.len);
+        match self.handles[i] {
+            Handle::Live => match kani::any::<u8>() {
+                0 if self.len < MAX_HANDLES => {
+                    self.counters().register();
+                    self.handles[self.len] = Handle::Live;
+                    self.len += 1;
+                }
+                1 => {
+                    self.counters().arrive();
+                    self.handles[i] = Handle::Dropping;
+                }
+                _ => {
+                    self.counters().arrive();
+                    self.handles[i] = Handle::Waiting;
+                }
+            },
+            Handle::Dropping => {
+                self.release();
+                self.handles[i] = Handle::Gone;
+            }
+            Handle::Waiting => {
+                if kani::any() {
+                    // Times out, which cancels the arrival unless there is
+                    // no live handle anymore.
+                    if self.counters().unarrive() {
+                        self.handles[i] = Handle::Live;
+                        return;
+                    }
+                }
+                kani::assume(self.counters().live() == 0);
+                if self.counters().elect() {
+                    self.leaders += 1;
+                }
+                self.release();
+                self.handles[i] = Handle::Gone;
+            }
+            Handle::Gone => kani::assume(false),
+        }
+        if !self.freed {
+            assert!(self.counters.live() <= self.counters.alloc_dep());
+        }
+    }
+}
+
+/// Clones, drops and waits, including timed out ones, never access the
+/// counters once freed, free them at most once, and free them once all the
+/// handles are gone, electing at most one leader.
+#[kani::proof]
+#[kani::unwind(11)]
+fn clone_drop_wait() {
+    let n: usize = kani::any();
+    kani::assume((1..=MAX_HANDLES).contains(&n));
+    let mut model = Model::new(n);
+    for _ in 0..MAX_STEPS {
+        if model.is_done() {
+            break;
+        }
+        model.step();
+    }
+    assert!(model.leaders <= 1);
+    if model.is_done() {
+        assert!(model.freed, "leak");
+    }
+}
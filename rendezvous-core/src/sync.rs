@@ -0,0 +1,91 @@
+//! The atomics of the protocol, and a [`Futex`](crate::Futex) for them,
+//! taken from loom under `cfg(loom)` so that the protocol can be
+//! model-checked, and from portable-atomic with the `portable-atomic`
+//! feature on targets without native read-modify-write operations.
+
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::{fence, AtomicU32, Ordering};
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{fence, AtomicU32, Ordering};
+
+/// The atomics the counters are modified through, while [`AtomicU32`] is the
+/// one of the [`Futex`](crate::Futex) trait, which is only loaded.
+pub(crate) mod rmw {
+    #[cfg(all(not(loom), not(feature = "portable-atomic")))]
+    pub(crate) use core::sync::atomic::AtomicU32;
+    #[cfg(all(not(loom), not(feature = "portable-atomic"), feature = "u64-counters"))]
+    pub(crate) use core::sync::atomic::AtomicU64;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::AtomicU32;
+    #[cfg(all(loom, feature = "u64-counters"))]
+    pub(crate) use loom::sync::atomic::AtomicU64;
+    #[cfg(all(not(loom), feature = "portable-atomic"))]
+    pub(crate) use portable_atomic::AtomicU32;
+    #[cfg(all(not(loom), feature = "portable-atomic", feature = "u64-counters"))]
+    pub(crate) use portable_atomic::AtomicU64;
+}
+
+/// The atomic holding a [`Count`](crate::Count).
+#[cfg(not(feature = "u64-counters"))]
+pub(crate) type AtomicCount = rmw::AtomicU32;
+#[cfg(feature = "u64-counters")]
+pub(crate) type AtomicCount = rmw::AtomicU64;
+
+/// Returns `atomic` as the atomic of the [`Futex`](crate::Futex) trait.
+#[cfg(any(loom, not(feature = "portable-atomic")))]
+pub(crate) fn futex_atomic(atomic: &rmw::AtomicU32) -> &AtomicU32 {
+    atomic
+}
+
+/// Returns `atomic` as the atomic of the [`Futex`](crate::Futex) trait.
+#[cfg(all(not(loom), feature = "portable-atomic"))]
+pub(crate) fn futex_atomic(atomic: &rmw::AtomicU32) -> &AtomicU32 {
+    // Safety: both have the representation of a u32, aligned to 4 bytes,
+    // and the native atomic is only loaded, which even targets without
+    // read-modify-write operations do atomically.
+    unsafe { AtomicU32::from_ptr(atomic.as_ptr()) }
+}
+
+/// Declares a function which is `const`, except under loom whose atomics
+/// cannot be created in const contexts.
+macro_rules! const_fn {
+    ($(#[$attr:meta])* $vis:vis const fn $name:ident $($rest:tt)*) => {
+        $(#[$attr])*
+        #[cfg(not(loom))]
+        $vis const fn $name $($rest)*
+
+        $(#[$attr])*
+        #[cfg(loom)]
+        $vis fn $name $($rest)*
+    };
+}
+
+/// A [`Futex`](crate::Futex) for loom models, which yields to the other
+/// threads of the model instead of blocking.
+///
+/// # Remarks
+///
+/// - This type is only available under `cfg(loom)`.
+#[cfg(loom)]
+#[derive(Debug)]
+pub struct LoomFutex;
+
+#[cfg(loom)]
+impl crate::Futex for LoomFutex {
+    fn wait(atomic: &AtomicU32, value: u32) {
+        // Returning spuriously is allowed, the callers check again.
+        if atomic.load(Ordering::Acquire) == value {
+            loom::thread::yield_now();
+        }
+    }
+
+    fn wake_all(_atomic: *const AtomicU32) {}
+}
+
+#[cfg(loom)]
+impl crate::TimedFutex for LoomFutex {
+    fn wait_timeout(atomic: &AtomicU32, value: u32, _timeout: core::time::Duration) {
+        // Loom has no notion of time, any wait may time out.
+        <Self as crate::Futex>::wait(atomic, value);
+    }
+}
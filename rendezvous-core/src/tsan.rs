@@ -0,0 +1,40 @@
+//! ThreadSanitizer annotations of the happens-before edges of the protocol,
+//! enabled with `--cfg rendezvous_tsan`.
+
+use crate::sync::AtomicCount;
+
+#[cfg(rendezvous_tsan)]
+mod ffi {
+    use core::ffi::c_void;
+
+    extern "C" {
+        pub fn __tsan_acquire(addr: *mut c_void);
+        pub fn __tsan_release(addr: *mut c_void);
+    }
+}
+
+/// Marks everything which happened before the matching releases on `atomic`
+/// as happening before what follows.
+#[inline(always)]
+pub(crate) fn acquire(atomic: &AtomicCount) {
+    #[cfg(rendezvous_tsan)]
+    // SAFETY: TSan only uses the address as a key.
+    unsafe {
+        ffi::__tsan_acquire(atomic.as_ptr().cast())
+    };
+    #[cfg(not(rendezvous_tsan))]
+    let _ = atomic;
+}
+
+/// Marks everything which happened before as happening before the matching
+/// acquires on `atomic`.
+#[inline(always)]
+pub(crate) fn release(atomic: &AtomicCount) {
+    #[cfg(rendezvous_tsan)]
+    // SAFETY: TSan only uses the address as a key.
+    unsafe {
+        ffi::__tsan_release(atomic.as_ptr().cast())
+    };
+    #[cfg(not(rendezvous_tsan))]
+    let _ = atomic;
+}
@@ -0,0 +1,234 @@
+//! Model-checks the protocol with loom, see the crate documentation.
+
+#![cfg(loom)]
+
+use std::{mem::ManuallyDrop, ptr};
+
+use loom::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use rendezvous_core::{Count, Counters, LoomFutex};
+
+/// A handle on counters shared through an `Arc`, which stands for the
+/// allocation: the handle releasing the last allocation dependency records
+/// that it freed it instead.
+struct Handle {
+    counters: Arc<Counters>,
+    freed: Arc<AtomicUsize>,
+}
+
+impl Handle {
+    fn new() -> Self {
+        Self {
+            counters: Arc::new(Counters::new()),
+            freed: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn clone(&self) -> Self {
+        self.counters.register();
+        Self {
+            counters: self.counters.clone(),
+            freed: self.freed.clone(),
+        }
+    }
+
+    fn arrive(&self) -> Count {
+        let live = self.counters.arrive();
+        if live == 0 {
+            self.counters.wake_all::<LoomFutex>();
+        }
+        live
+    }
+
+    fn release(&self) {
+        if self.counters.release() {
+            self.freed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether this handle is the leader.
+    fn wait(self) -> bool {
+        let live = self.arrive();
+        self.counters.wait::<LoomFutex>(live);
+        let leader = self.counters.elect();
+        self.release();
+        self.disarm();
+        leader
+    }
+
+    /// Drops the handle without arriving nor releasing again.
+    fn disarm(self) {
+        let this = ManuallyDrop::new(self);
+        // Safety: the fields are read once each and `this` is never dropped.
+        unsafe {
+            drop(ptr::read(&this.counters));
+            drop(ptr::read(&this.freed));
+        }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.arrive();
+        self.release();
+    }
+}
+
+#[test]
+fn wait_sees_the_work_of_dropped_handles() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        let work = Arc::new(AtomicUsize::new(0));
+        let worker = rdv.clone();
+        let thread = {
+            let work = work.clone();
+            thread::spawn(move || {
+                work.store(1, Ordering::Relaxed);
+                drop(worker);
+            })
+        };
+        let freed = rdv.freed.clone();
+        assert!(rdv.wait());
+        assert_eq!(work.load(Ordering::Relaxed), 1);
+        thread.join().unwrap();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn exactly_one_waiter_is_the_leader() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        let other = rdv.clone();
+        let freed = rdv.freed.clone();
+        let thread = thread::spawn(move || other.wait());
+        let leaders = usize::from(rdv.wait()) + usize::from(thread.join().unwrap());
+        assert_eq!(leaders, 1);
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn clones_from_other_threads_are_waited_for() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        let work = Arc::new(AtomicUsize::new(0));
+        let worker = rdv.clone();
+        let thread = {
+            let work = work.clone();
+            thread::spawn(move || {
+                // Cloned while the first reference may already wait.
+                let nested = worker.clone();
+                drop(worker);
+                work.store(1, Ordering::Relaxed);
+                drop(nested);
+            })
+        };
+        rdv.wait();
+        assert_eq!(work.load(Ordering::Relaxed), 1);
+        thread.join().unwrap();
+    });
+}
+
+#[test]
+fn cancelled_arrivals_stay_live() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        let worker = rdv.clone();
+        let freed = rdv.freed.clone();
+        let thread = thread::spawn(move || drop(worker));
+        let live = rdv.counters.arrive_cancellable();
+        // Gives up right away, racing with the arrival of the worker.
+        let complete =
+            rdv.counters
+                .wait_deadline::<LoomFutex>(live, Default::default(), Default::default);
+        thread.join().unwrap();
+        if complete {
+            assert_eq!(rdv.counters.live(), 0);
+            rdv.release();
+            rdv.disarm();
+        } else {
+            // Live again, and now the last one.
+            assert_eq!(rdv.counters.live(), 1);
+            drop(rdv);
+        }
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    });
+}
+
+#[test]
+fn waiting_for_the_others_ignores_cancelled_arrivals() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        let worker = rdv.clone();
+        let gave_up = Arc::new(AtomicUsize::new(0));
+        let thread = {
+            let gave_up = gave_up.clone();
+            thread::spawn(move || {
+                let live = worker.counters.arrive_cancellable();
+                // Gives up right away, since the first handle stays live.
+                let complete = worker.counters.wait_deadline::<LoomFutex>(
+                    live,
+                    Default::default(),
+                    Default::default,
+                );
+                assert!(!complete);
+                gave_up.store(1, Ordering::Relaxed);
+                drop(worker);
+            })
+        };
+        rdv.counters.wait_others::<LoomFutex>();
+        assert_eq!(gave_up.load(Ordering::Relaxed), 1);
+        thread.join().unwrap();
+    });
+}
+
+#[test]
+fn upgrades_cannot_join_the_last_handle_acting() {
+    loom::model(|| {
+        let rdv = Handle::new();
+        // A weak handle, which only owns an allocation dependency.
+        rdv.counters.retain();
+        let freed = rdv.freed.clone();
+        let ran = Arc::new(AtomicUsize::new(0));
+        let thread = {
+            let (counters, freed) = (rdv.counters.clone(), rdv.freed.clone());
+            thread::spawn(move || {
+                let weak = Handle { counters, freed };
+                weak.counters.retain();
+                let upgraded = weak.counters.register_retained();
+                if upgraded {
+                    weak.arrive();
+                    weak.release();
+                }
+                weak.release();
+                weak.disarm();
+                upgraded
+            })
+        };
+        // Like wait_and_then, running the critical section if last.
+        match rdv.counters.arrive_if_not_last() {
+            Some(live) => {
+                rdv.counters.wait::<LoomFutex>(live);
+                rdv.release();
+                rdv.disarm();
+            }
+            None => {
+                assert_eq!(rdv.counters.live(), 1);
+                ran.store(1, Ordering::Relaxed);
+                rdv.counters.finish();
+                rdv.counters.wake_all::<LoomFutex>();
+                rdv.release();
+                rdv.disarm();
+            }
+        }
+        let upgraded = thread.join().unwrap();
+        assert_eq!(usize::from(upgraded) + ran.load(Ordering::Relaxed), 1);
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    });
+}
@@ -0,0 +1,105 @@
+//! Arriving at a rendezvous early and waiting for the others later.
+
+use core::{fmt, ptr::NonNull};
+
+use crate::{release, RDVInner, Rendezvous};
+
+/// A reference which arrived at its [`Rendezvous`] but can still wait for
+/// the others, created by [`Rendezvous::arrive`].
+pub struct Arrived {
+    ptr: NonNull<RDVInner>,
+}
+
+impl Rendezvous {
+    /// Arrives at the rendezvous without waiting, as if this reference was
+    /// dropped, but keeps the possibility to wait for the others later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// thread::spawn(move || {
+    ///     // Do our part of the work.
+    ///     let arrived = worker.arrive();
+    ///     // Do some work the others do not depend on.
+    ///     arrived.await_others().unwrap();
+    /// });
+    /// rdv.wait().unwrap();
+    /// ```
+    pub fn arrive(self) -> Arrived {
+        let ptr = self.into_ptr();
+        // Safety: we forgot self so we own its allocation dependency, which
+        // is handed over to the arrived reference.
+        unsafe { ptr.as_ref() }.arrive();
+        Arrived { ptr }
+    }
+}
+
+impl Arrived {
+    /// Returns whether all the other references are dropped, in which case
+    /// [`await_others`](Arrived::await_others) does not block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// let arrived = rdv.arrive();
+    /// assert!(!arrived.is_complete());
+    /// drop(worker);
+    /// assert!(arrived.is_complete());
+    /// ```
+    pub fn is_complete(&self) -> bool {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.counters.live() == 0
+    }
+
+    /// Waits until all the other references are dropped, as the second half
+    /// of [`Rendezvous::wait`].
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn await_others(self) -> Result<crate::WaitResult, crate::error::Poisoned> {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        // Safety: we forgot self so we own its allocation dependency.
+        let inner = unsafe { ptr.as_ref() };
+        crate::hooks::wait(inner, inner.counters.live());
+        // Safety: we own an allocation dependency, which we do not use
+        // afterwards.
+        unsafe { crate::finish(ptr) }
+    }
+}
+
+impl Drop for Arrived {
+    fn drop(&mut self) {
+        // Safety: self owns an allocation dependency it does not use
+        // afterwards.
+        unsafe { release(self.ptr) };
+    }
+}
+
+// Safety: arrived references only touch the counters, which are atomic.
+unsafe impl Send for Arrived {}
+// Safety: same as above.
+unsafe impl Sync for Arrived {}
+
+impl fmt::Debug for Arrived {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        f.debug_struct("Arrived")
+            .field("live barriers", &inner.counters.live())
+            .finish()
+    }
+}
@@ -0,0 +1,81 @@
+//! The wait backend of the crate without the `std` feature, see
+//! [`set_backend`].
+
+use core::{
+    hint, ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::AtomicPtr;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::AtomicPtr;
+use rendezvous_core::Futex;
+
+/// The functions of the [`Futex`] set as the backend.
+struct Backend {
+    wait: fn(&AtomicU32, u32),
+    wake_all: fn(*const AtomicU32),
+}
+
+/// Null until a backend is set.
+static BACKEND: AtomicPtr<Backend> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets the [`Futex`] the blocking waits of the crate go through without
+/// the `std` feature, e.g. one built on the park and unpark primitives of an
+/// RTOS.
+///
+/// # Panics
+///
+/// If a backend was set already.
+///
+/// # Remarks
+///
+/// - Until a backend is set, the waits spin on the atomics.
+/// - The [`wake_all`](Futex::wake_all) of the backend is called by the last
+///   participant to arrive, or by one leaving a single live one, e.g. from
+///   interrupt context with an [`IsrToken`](crate::IsrToken).
+/// - This function is only available without the `std` and `spin`
+///   features.
+pub fn set_backend<F: Futex>() {
+    let backend: &'static Backend = const {
+        &Backend {
+            wait: F::wait,
+            wake_all: F::wake_all,
+        }
+    };
+    assert!(
+        BACKEND
+            .compare_exchange(
+                ptr::null_mut(),
+                ptr::from_ref(backend).cast_mut(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok(),
+        "The wait backend should only be set once."
+    );
+}
+
+fn backend() -> Option<&'static Backend> {
+    // Safety: the pointer is either null or from a static reference.
+    unsafe { BACKEND.load(Ordering::Acquire).as_ref() }
+}
+
+pub(crate) fn wait(atomic: &AtomicU32, value: u32) {
+    match backend() {
+        Some(backend) => (backend.wait)(atomic, value),
+        // Returning spuriously is allowed, the callers check again.
+        None => {
+            if atomic.load(Ordering::Relaxed) == value {
+                hint::spin_loop();
+            }
+        }
+    }
+}
+
+pub(crate) fn wake_all(atomic: *const AtomicU32) {
+    if let Some(backend) = backend() {
+        (backend.wake_all)(atomic);
+    }
+}
@@ -0,0 +1,291 @@
+//! A reusable barrier with dynamic membership, timeouts and poisoning.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use rendezvous_core::{Futex as _, TimedFutex as _};
+
+use crate::{error::Poisoned, futex::Futex};
+
+/// A reusable barrier whose participants are its handles: cloning one joins
+/// the barrier and dropping one leaves it, even in the middle of a
+/// generation.
+///
+/// Unlike [`ReusableRendezvous`](crate::ReusableRendezvous), its waits can
+/// time out, and a participant dropped while panicking poisons the current
+/// generation: the participants waiting in it are released with
+/// [`Poisoned`]. The following generations are not affected.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Barrier;
+/// use std::{thread, time::Duration};
+///
+/// let barrier = Barrier::new();
+/// let worker = barrier.clone();
+/// thread::spawn(move || {
+///     let _worker = worker;
+///     panic!("the worker failed");
+/// });
+/// // Either the worker panicked in this generation, or it left beforehand.
+/// let _ = barrier.wait();
+/// // Without any other participant, this one does not wait.
+/// assert_eq!(barrier.wait_timeout(Duration::from_secs(1)), Ok(Some(true)));
+/// ```
+///
+/// # Remarks
+///
+/// - There cannot be more than 2²⁰ - 1 simultaneous copies of a single
+///   barrier.
+/// - Each handle must wait from one thread at a time.
+pub struct Barrier {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // From the least significant bits: the number of participants which
+    // arrived in the current generation, the number of participants, the
+    // generation modulo 2¹⁶ and the number of poisoned generations since the
+    // last one which completed normally, saturating at 255.
+    state: AtomicU64,
+    // Bumped after each generation, for the waiters to block on.
+    generation: AtomicU32,
+}
+
+const COUNT_BITS: u32 = 20;
+const COUNT_MASK: u64 = (1 << COUNT_BITS) - 1;
+const ONE_PARTICIPANT: u64 = 1 << COUNT_BITS;
+const TAG_SHIFT: u32 = 2 * COUNT_BITS;
+const TAG_MASK: u64 = (1 << 16) - 1;
+const POISONED_SHIFT: u32 = TAG_SHIFT + 16;
+const POISONED_MASK: u64 = (1 << 8) - 1;
+
+fn arrived(state: u64) -> u64 {
+    state & COUNT_MASK
+}
+
+fn participants(state: u64) -> u64 {
+    (state >> COUNT_BITS) & COUNT_MASK
+}
+
+fn tag(state: u64) -> u64 {
+    (state >> TAG_SHIFT) & TAG_MASK
+}
+
+fn poisoned(state: u64) -> u64 {
+    (state >> POISONED_SHIFT) & POISONED_MASK
+}
+
+/// Returns the state starting the generation following the one of `state`.
+fn advance(state: u64, poisoned: bool) -> u64 {
+    let tag = (tag(state) + 1) & TAG_MASK;
+    let poisoned = if poisoned {
+        (self::poisoned(state) + 1).min(POISONED_MASK)
+    } else {
+        0
+    };
+    poisoned << POISONED_SHIFT | tag << TAG_SHIFT | participants(state) << COUNT_BITS
+}
+
+/// Returns whether the generation whose tag was `tag` was poisoned, `state`
+/// being from a later one.
+///
+/// Its waiters are participants of the following generations which did not
+/// arrive yet, so these can only have been poisoned since.
+fn was_poisoned(state: u64, tag: u64) -> bool {
+    let later = (self::tag(state).wrapping_sub(tag) & TAG_MASK) - 1;
+    poisoned(state) > later
+}
+
+impl Inner {
+    /// Releases the waiters of the generation which just completed.
+    fn wake(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+        Futex::wake_all(&self.generation);
+    }
+}
+
+impl Barrier {
+    /// Creates a new `Barrier` with a single participant. Clone it so that
+    /// other threads can synchronize on it.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: AtomicU64::new(ONE_PARTICIPANT),
+                generation: AtomicU32::new(0),
+            }),
+        }
+    }
+
+    /// Blocks until all the participants waited in the current generation,
+    /// or left. Returns whether this participant was the last one to wait,
+    /// which started the next generation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Barrier;
+    /// use std::thread;
+    ///
+    /// let barrier = Barrier::new();
+    /// let worker = barrier.clone();
+    /// let worker = thread::spawn(move || {
+    ///     worker.wait().unwrap();
+    ///     let failing = worker.clone();
+    ///     thread::spawn(move || {
+    ///         let _failing = failing;
+    ///         panic!("the failing participant panicked");
+    ///     });
+    ///     // Poisoned if the failing participant panicked after this wait
+    ///     // started, and completed by the end of the first one otherwise.
+    ///     let _ = worker.wait();
+    /// });
+    /// // Whenever the failing participant panicked, this generation was over.
+    /// assert!(barrier.wait().is_ok());
+    /// drop(barrier);
+    /// worker.join().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If a participant panicked during the generation.
+    pub fn wait(&self) -> Result<bool, Poisoned> {
+        self.wait_until(None)
+            .map(|leader| leader.unwrap_or_default())
+    }
+
+    /// Same as [`Barrier::wait`], but gives up once `timeout` has elapsed,
+    /// returning `Ok(None)`. This participant then did not take part in the
+    /// generation, as if it had not waited.
+    ///
+    /// # Errors
+    ///
+    /// If a participant panicked during the generation.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<bool>, Poisoned> {
+        self.wait_until(Some(Instant::now() + timeout))
+    }
+
+    /// Returns the number of generations completed so far, wrapping around.
+    pub fn generation(&self) -> u32 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+
+    fn wait_until(&self, deadline: Option<Instant>) -> Result<Option<bool>, Poisoned> {
+        let inner = &*self.inner;
+        let prev = inner
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                assert!(
+                    arrived(s) < participants(s),
+                    "Each handle of a Barrier should wait from one thread at a time."
+                );
+                Some(if arrived(s) + 1 == participants(s) {
+                    advance(s, false)
+                } else {
+                    s + 1
+                })
+            })
+            .unwrap();
+        if arrived(prev) + 1 == participants(prev) {
+            inner.wake();
+            return Ok(Some(true));
+        }
+        let tag = tag(prev);
+        loop {
+            // Read before the state, so that the generation cannot complete
+            // unnoticed in between.
+            let generation = inner.generation.load(Ordering::Acquire);
+            let state = inner.state.load(Ordering::Acquire);
+            if self::tag(state) != tag {
+                return if was_poisoned(state, tag) {
+                    Err(Poisoned { panicked: 1 })
+                } else {
+                    Ok(Some(false))
+                };
+            }
+            let Some(deadline) = deadline else {
+                Futex::wait(&inner.generation, generation);
+                continue;
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                let cancelled = inner
+                    .state
+                    .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                        (self::tag(s) == tag).then(|| s - 1)
+                    })
+                    .is_ok();
+                if cancelled {
+                    return Ok(None);
+                }
+                // The generation completed in the meantime.
+                continue;
+            }
+            Futex::wait_timeout(&inner.generation, generation, remaining);
+        }
+    }
+}
+
+impl Drop for Barrier {
+    fn drop(&mut self) {
+        let inner = &*self.inner;
+        let panicking = std::thread::panicking();
+        // Returns the state after leaving, and whether it starts the next
+        // generation.
+        let leave = |s: u64| {
+            let s = s - ONE_PARTICIPANT;
+            // The others may only have been waiting for us, and there is no
+            // generation to poison until one of them waits.
+            if arrived(s) > 0 && (panicking || arrived(s) == participants(s)) {
+                (advance(s, panicking), true)
+            } else {
+                (s, false)
+            }
+        };
+        let prev = inner
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| Some(leave(s).0))
+            .unwrap();
+        if leave(prev).1 {
+            inner.wake();
+        }
+    }
+}
+
+impl Clone for Barrier {
+    fn clone(&self) -> Self {
+        self.inner
+            .state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |s| {
+                (participants(s) < COUNT_MASK).then_some(s + ONE_PARTICIPANT)
+            })
+            .expect("There should not be more than 2^20 - 1 clones of one Barrier.");
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Default for Barrier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.load(Ordering::Relaxed);
+        f.debug_struct("Barrier")
+            .field("participants", &participants(state))
+            .field("arrived", &arrived(state))
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
@@ -0,0 +1,162 @@
+//! Broadcasting a value from the last participant to the waiters.
+//!
+//! Values are shared through an [`Arc`], which needs atomic pointers, so
+//! that broadcasting is not available on targets without them.
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+#[cfg(target_has_atomic = "ptr")]
+use core::any::Any;
+use core::cell::UnsafeCell;
+#[cfg(target_has_atomic = "ptr")]
+use core::mem::ManuallyDrop;
+
+#[cfg(target_has_atomic = "ptr")]
+use crate::{release, Finishing, Observer, Rendezvous};
+
+/// The value published by the last live reference, if any.
+///
+/// Only written by the last live reference before it arrives, and only read
+/// once there is no live reference anymore.
+#[cfg(target_has_atomic = "ptr")]
+pub(crate) type Published = UnsafeCell<Option<Arc<dyn Any + Send + Sync>>>;
+/// Never set without atomic pointers.
+#[cfg(not(target_has_atomic = "ptr"))]
+pub(crate) type Published = UnsafeCell<Option<core::convert::Infallible>>;
+
+#[cfg(target_has_atomic = "ptr")]
+impl Rendezvous {
+    /// Drops this reference and, if it is the last one, publishes the value
+    /// returned by `f` to the references waiting with
+    /// [`wait_recv`](Rendezvous::wait_recv). Returns whether the value was
+    /// published.
+    ///
+    /// `f` only runs if this reference is the last one, so that partial
+    /// results can be summed up once all participants are done, and never
+    /// if the rendezvous is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::{
+    ///     sync::{
+    ///         atomic::{AtomicU64, Ordering},
+    ///         Arc,
+    ///     },
+    ///     thread,
+    /// };
+    ///
+    /// let total = Arc::new(AtomicU64::new(0));
+    /// let rdv = Rendezvous::new();
+    /// // The observer never is the last reference, unlike rdv.
+    /// let observer = rdv.observer();
+    /// for i in 1..=4 {
+    ///     let (rdv, total) = (rdv.clone(), total.clone());
+    ///     thread::spawn(move || {
+    ///         total.fetch_add(i, Ordering::Relaxed);
+    ///         rdv.finish_with(|| format!("total: {}", total.load(Ordering::Relaxed)));
+    ///     });
+    /// }
+    /// // This reference may be the last one too.
+    /// rdv.finish_with(|| format!("total: {}", total.load(Ordering::Relaxed)));
+    /// let summary = observer.recv::<String>().unwrap().unwrap();
+    /// assert_eq!(*summary, "total: 10");
+    /// ```
+    pub fn finish_with<T, F>(self, f: F) -> bool
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> T,
+    {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        if inner.arrive_if_not_last().is_some() {
+            // Safety: we arrived so we only own our allocation dependency.
+            unsafe { release(self.into_ptr()) };
+            return false;
+        }
+        // We are the last live reference, nobody can join, read the value
+        // nor poison the rendezvous before we finish.
+        let _finishing = Finishing(ManuallyDrop::new(self));
+        if inner.counters.is_poisoned() {
+            return false;
+        }
+        let value = Arc::new(f());
+        // Safety: see Published.
+        unsafe { *inner.published.get() = Some(value) };
+        true
+    }
+
+    /// Drops this reference, waits until all other references are dropped,
+    /// and returns the value published with
+    /// [`finish_with`](Rendezvous::finish_with), if any.
+    ///
+    /// `None` is returned if the last reference did not publish a value, or
+    /// if the published value is not a `T`. In particular, this reference
+    /// may be the last one, see [`Observer::recv`] to always receive the
+    /// value.
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    ///
+    /// # Remarks
+    ///
+    /// - This does not take part in the [leader](crate::WaitResult::is_leader)
+    ///   election.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait_recv<T>(self) -> Result<Option<Arc<T>>, crate::error::Poisoned>
+    where
+        T: Any + Send + Sync,
+    {
+        let ptr = self.into_ptr();
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.ptr is still valid
+        let (result, mode) = {
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { ptr.as_ref() };
+            let l = inner.arrive();
+            crate::hooks::wait(inner, l);
+            let result = inner.check_poisoned().map(|()| {
+                // Safety: see Published.
+                let published = unsafe { &*inner.published.get() };
+                published.clone().and_then(|value| value.downcast().ok())
+            });
+            (result, inner.counters.panic_mode())
+        };
+        // Safety: the invariant from the scope above is still true and we
+        // forgot self so we own its allocation dependency.
+        unsafe { release(ptr) };
+        crate::report(result, mode)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Observer {
+    /// Blocks until all the references to the rendezvous are dropped, and
+    /// returns the value published with
+    /// [`finish_with`](Rendezvous::finish_with), if any.
+    ///
+    /// `None` is returned if the last reference did not publish a value, or
+    /// if the published value is not a `T`.
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn recv<T>(&self) -> Result<Option<Arc<T>>, crate::error::Poisoned>
+    where
+        T: Any + Send + Sync,
+    {
+        self.wait()?;
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        // Safety: see Published.
+        let published = unsafe { &*inner.published.get() };
+        Ok(published.clone().and_then(|value| value.downcast().ok()))
+    }
+}
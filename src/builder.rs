@@ -0,0 +1,129 @@
+//! Creating rendezvous with several options.
+
+use alloc::{boxed::Box, string::String};
+use core::fmt::Debug;
+
+use rendezvous_core::Counters;
+
+use crate::{Count, PanicMode, Rendezvous};
+
+/// Creates a [`Rendezvous`] with non-default options.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::{PanicMode, Rendezvous};
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// let rdv = Rendezvous::builder()
+///     .panic_mode(PanicMode::Poison)
+///     .on_complete(move || tx.send("done").unwrap())
+///     .build();
+/// drop(rdv);
+/// assert_eq!(rx.recv(), Ok("done"));
+/// ```
+#[derive(Default)]
+pub struct Builder {
+    panic_mode: PanicMode,
+    name: String,
+    on_complete: Option<Box<dyn FnOnce() + Send>>,
+    on_progress: Option<Box<dyn Fn(Count) + Send + Sync>>,
+}
+
+impl Builder {
+    /// Creates a builder with the default options, those of
+    /// [`Rendezvous::new`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the panics of the participants are handled, see
+    /// [`Rendezvous::with_panic_mode`].
+    pub fn panic_mode(mut self, mode: PanicMode) -> Self {
+        self.panic_mode = mode;
+        self
+    }
+
+    /// Sets the name of the rendezvous, see [`Rendezvous::named`].
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets a callback run exactly once, by the last reference to arrive,
+    /// whether it is dropped or waits.
+    ///
+    /// # Remarks
+    ///
+    /// - The callback runs on the thread of the last reference, after the
+    ///   waiters have been woken up: they may return before it does.
+    /// - If the last reference is an [`IsrToken`](crate::IsrToken), the
+    ///   callback runs in interrupt context.
+    /// - If the callback panics, the panic propagates out of the drop or wait
+    ///   of the last reference, once the links to the completion, e.g. the
+    ///   rendezvous [merged](Rendezvous::merge) with this one, were released.
+    ///   The allocation of the rendezvous is then leaked.
+    pub fn on_complete<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a callback run each time references arrive, whether they are
+    /// dropped or wait, with the number of references still live.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::builder()
+    ///     .on_progress(|remaining| println!("{remaining} workers still running"))
+    ///     .build();
+    /// for _ in 0..4 {
+    ///     rdv.spawn(|| ());
+    /// }
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - The callback runs on the threads of the arriving references,
+    ///   possibly concurrently, and the counts it is given may be reported
+    ///   out of order.
+    /// - The callback given zero runs before the waiters are woken up.
+    /// - The callback runs in interrupt context for the arrivals of
+    ///   [`IsrToken`](crate::IsrToken)s.
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Count) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /// Creates the rendezvous.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn build(self) -> Rendezvous {
+        Rendezvous::from_parts(
+            Counters::with_panic_mode(self.panic_mode),
+            self.name.into_boxed_str(),
+            self.on_complete,
+            self.on_progress,
+        )
+    }
+}
+
+impl Debug for Builder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Builder")
+            .field("panic_mode", &self.panic_mode)
+            .field("name", &self.name)
+            .field("on_complete", &self.on_complete.is_some())
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
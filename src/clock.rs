@@ -0,0 +1,100 @@
+//! Clock sources used to measure the timeouts of timed waits.
+
+use std::time::Duration;
+
+/// The clock against which timeouts are measured.
+///
+/// The two clocks only differ in how they account for time spent while the
+/// system is suspended: a timeout of one minute measured on
+/// [`Clock::Monotonic`] can last for hours if the machine goes to sleep in
+/// the meantime, while on [`Clock::Boottime`] it expires within about a second
+/// of the system resuming: waiters only re-check the clock once a second at
+/// most, the native timeouts not accounting for the suspension.
+///
+/// # Platform mappings
+///
+/// | Platform        | `Monotonic`         | `Boottime`          |
+/// |-----------------|---------------------|---------------------|
+/// | Linux, Android  | `CLOCK_MONOTONIC`   | `CLOCK_BOOTTIME`    |
+/// | macOS, iOS      | `CLOCK_UPTIME_RAW`  | `CLOCK_MONOTONIC`   |
+/// | Others          | [`Instant`]         | [`Instant`]         |
+///
+/// [`Instant`]: std::time::Instant
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Clock {
+    /// A clock that does not advance while the system is suspended.
+    #[default]
+    Monotonic,
+    /// A clock that keeps advancing while the system is suspended.
+    Boottime,
+}
+
+impl Clock {
+    /// Returns the current reading of this clock, as a duration since an
+    /// unspecified epoch.
+    ///
+    /// Readings are only meaningful when compared to other readings of the
+    /// same clock.
+    pub fn now(self) -> Duration {
+        imp::now(self)
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+))]
+mod imp {
+    use std::time::Duration;
+
+    use super::Clock;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn clock_id(clock: Clock) -> libc::clockid_t {
+        match clock {
+            Clock::Monotonic => libc::CLOCK_MONOTONIC,
+            Clock::Boottime => libc::CLOCK_BOOTTIME,
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    fn clock_id(clock: Clock) -> libc::clockid_t {
+        match clock {
+            Clock::Monotonic => libc::CLOCK_UPTIME_RAW,
+            Clock::Boottime => libc::CLOCK_MONOTONIC,
+        }
+    }
+
+    pub(super) fn now(clock: Clock) -> Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: ts is a valid, writable timespec.
+        let res = unsafe { libc::clock_gettime(clock_id(clock), &mut ts) };
+        assert_eq!(res, 0, "clock_gettime failed on a supported clock");
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+mod imp {
+    use std::{
+        sync::OnceLock,
+        time::{Duration, Instant},
+    };
+
+    use super::Clock;
+
+    pub(super) fn now(_clock: Clock) -> Duration {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed()
+    }
+}
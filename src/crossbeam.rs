@@ -0,0 +1,55 @@
+//! Crossbeam scoped threads holding a reference to a rendezvous.
+
+use crossbeam_utils::thread::{Scope, ScopedJoinHandle};
+
+use crate::Rendezvous;
+
+/// Spawns crossbeam scoped threads while holding a reference to a
+/// rendezvous.
+///
+/// Waiting on the rendezvous outside of the scope then covers both the
+/// scoped threads and the detached ones, e.g. spawned with
+/// [`Rendezvous::spawn`].
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::{Rendezvous, ScopeExt};
+///
+/// let rdv = Rendezvous::new();
+/// let mut data = vec![1, 2, 3];
+/// crossbeam_utils::thread::scope(|s| {
+///     s.spawn_with(&rdv, |_| data.push(4));
+/// })
+/// .unwrap();
+/// rdv.spawn(|| println!("detached"));
+/// rdv.wait().unwrap();
+/// assert_eq!(data, [1, 2, 3, 4]);
+/// ```
+pub trait ScopeExt<'env> {
+    /// Spawns a scoped thread running `f` while holding a new reference to
+    /// `rdv`, dropped once `f` returns or panics.
+    fn spawn_with<'scope, F, T>(
+        &'scope self,
+        rdv: &Rendezvous,
+        f: F,
+    ) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce(&Scope<'env>) -> T + Send + 'env,
+        T: Send + 'env;
+}
+
+impl<'env> ScopeExt<'env> for Scope<'env> {
+    fn spawn_with<'scope, F, T>(&'scope self, rdv: &Rendezvous, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce(&Scope<'env>) -> T + Send + 'env,
+        T: Send + 'env,
+    {
+        let rdv = rdv.clone();
+        self.spawn(move |scope| {
+            // Dropped after f, even if it panics.
+            let _rdv = rdv.adopt();
+            f(scope)
+        })
+    }
+}
@@ -0,0 +1,149 @@
+//! Threads waiting on a rendezvous while holding some of its references,
+//! for the `deadlock-detection` feature.
+//!
+//! Moving a reference to another thread cannot be observed, so each
+//! reference is deemed held by the thread it was created on, or by the
+//! thread it was handed to by the spawning helpers of the crate, e.g.
+//! [`Rendezvous::spawn`](crate::Rendezvous::spawn). The references the crate
+//! keeps until another rendezvous completes, e.g. to the parent of a
+//! [child](crate::Rendezvous::child), are deemed held by no thread. A
+//! blocking wait which lasts for a while with all the remaining references
+//! held by its own thread reports a likely deadlock, as a `tracing` event
+//! with the `tracing` feature and on the standard error otherwise, then
+//! keeps waiting.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{Mutex, MutexGuard, PoisonError},
+    thread::ThreadId,
+};
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+use std::{thread, time::Duration};
+
+use crate::{Count, RDVInner, Rendezvous};
+
+/// How long a wait lasts before being checked, and then between checks,
+/// so that references moved to other threads are likely dropped or
+/// cloned there by then.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+const GRACE: Duration = Duration::from_secs(1);
+
+/// The number of live references held by each thread.
+#[derive(Default)]
+pub(crate) struct Owners {
+    threads: Mutex<HashMap<ThreadId, Count>>,
+}
+
+impl Owners {
+    fn lock(&self) -> MutexGuard<'_, HashMap<ThreadId, Count>> {
+        // The map is consistent even if a thread panicked.
+        self.threads.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn add(&self, thread: ThreadId) {
+        *self.lock().entry(thread).or_default() += 1;
+    }
+
+    fn remove(&self, thread: ThreadId) {
+        if let Entry::Occupied(mut entry) = self.lock().entry(thread) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    fn held_by(&self, thread: ThreadId) -> Count {
+        self.lock().get(&thread).copied().unwrap_or(0)
+    }
+}
+
+fn inner(rdv: &Rendezvous) -> &RDVInner {
+    // Safety: rdv exist so the ptr is valid
+    unsafe { rdv.ptr.as_ref() }
+}
+
+pub(crate) fn created(rdv: &Rendezvous) {
+    inner(rdv).owners.add(rdv.owner);
+}
+
+pub(crate) fn gone(rdv: &Rendezvous) {
+    inner(rdv).owners.remove(rdv.owner);
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn moved(rdv: &mut Rendezvous) {
+    let thread = thread::current().id();
+    if rdv.owner != thread {
+        let owners = &inner(rdv).owners;
+        owners.remove(rdv.owner);
+        owners.add(thread);
+        rdv.owner = thread;
+    }
+}
+
+/// The deadlock detector of a blocking wait, see the hooks module.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct Detector {
+    thread: ThreadId,
+    // Since the start of the wait, None once reported.
+    next_check: Option<Duration>,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl Detector {
+    pub(crate) fn new() -> Self {
+        Self {
+            thread: thread::current().id(),
+            next_check: Some(GRACE),
+        }
+    }
+
+    /// Returns when to check the wait next, since its start.
+    pub(crate) fn next_check(&self) -> Option<Duration> {
+        self.next_check
+    }
+
+    /// Checks the wait, `elapsed` since its start, on `inner` which has
+    /// `live` live references.
+    pub(crate) fn check(&mut self, inner: &RDVInner, live: Count, elapsed: Duration) {
+        let Some(next_check) = self.next_check else {
+            return;
+        };
+        if elapsed < next_check {
+            return;
+        }
+        if inner.owners.held_by(self.thread) == live {
+            report(inner, live);
+            self.next_check = None;
+        } else {
+            self.next_check = Some(elapsed + GRACE);
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+#[cold]
+fn report(inner: &RDVInner, held: Count) {
+    let current = thread::current();
+    let thread = current.name().unwrap_or("<unnamed>");
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        name = &*inner.name,
+        thread,
+        held,
+        "rendezvous waited on by a thread holding all its remaining references"
+    );
+    #[cfg(not(feature = "tracing"))]
+    {
+        let name = match &*inner.name {
+            "" => String::new(),
+            name => format!(" `{name}`"),
+        };
+        eprintln!(
+            "rendezvous{name} waited on by thread `{thread}`, which holds its remaining \
+             references ({held}): likely deadlock"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Global registry of the live rendezvous of a process, for diagnosing
+//! deadlocks.
+//!
+//! With the `registry` feature, every [`Rendezvous`](crate::Rendezvous) is
+//! recorded from its creation until all its handles are gone, and [`dump`]
+//! returns the state of all of them, e.g. from a signal handler thread or
+//! an admin endpoint. The `ops` feature serves it over a Unix domain socket,
+//! see the `diagnostics` module.
+//!
+//! # Examples
+//!
+//! ```
+//! use rendezvous::Rendezvous;
+//!
+//! let rdv = Rendezvous::named("shutdown");
+//! let _worker = rdv.clone();
+//! for group in rendezvous::debug::dump() {
+//!     if let Some(name) = &group.name {
+//!         println!("{name}: {} live, {} waiting", group.live, group.waiting);
+//!     }
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    ptr::NonNull,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{Count, RDVInner};
+
+/// The inner allocations of all the live rendezvous, keyed by address.
+static REGISTRY: Mutex<BTreeMap<usize, Registered>> = Mutex::new(BTreeMap::new());
+
+struct Registered(NonNull<RDVInner>);
+
+// Safety: the registry only reads the counters of the inner, which are
+// atomics, and its name, which is immutable. Everything else may be
+// modified concurrently.
+unsafe impl Send for Registered {}
+
+pub(crate) fn register(ptr: NonNull<RDVInner>) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.insert(ptr.as_ptr() as usize, Registered(ptr));
+}
+
+/// Must be called before the inner is freed.
+pub(crate) fn unregister(ptr: NonNull<RDVInner>) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.remove(&(ptr.as_ptr() as usize));
+}
+
+/// Runs `f` with the registry locked, so that [`dump`] does not see the
+/// counters it modifies halfway.
+pub(crate) fn locked(f: impl FnOnce()) {
+    let _registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    f()
+}
+
+/// The state of a rendezvous at the time of a [`dump`] call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Group {
+    /// An identifier of the rendezvous, unique among the live ones.
+    pub id: usize,
+    /// The name of the rendezvous, see
+    /// [`Rendezvous::named`](crate::Rendezvous::named).
+    pub name: Option<String>,
+    /// The number of live handles.
+    pub live: Count,
+    /// The number of handles waiting for the live ones.
+    pub waiting: Count,
+    /// The number of threads blocked waiting for the live handles.
+    pub blocked: u32,
+}
+
+/// Returns the state of all the live rendezvous of the process, by
+/// increasing [`id`](Group::id).
+///
+/// Rendezvous are read one after the other, while they keep changing.
+pub fn dump() -> Vec<Group> {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry
+        .iter()
+        .map(|(&id, Registered(ptr))| {
+            // Safety: inners are unregistered, under the registry lock, before
+            // being freed, and only the atomics and the name are read.
+            let inner = unsafe { ptr.as_ref() };
+            let counters = &inner.counters;
+            let live = counters.live();
+            Group {
+                id,
+                name: Some(inner.name.to_string()).filter(|name| !name.is_empty()),
+                live,
+                waiting: counters.alloc_dep().saturating_sub(live),
+                blocked: counters.parked(),
+            }
+        })
+        .collect()
+}
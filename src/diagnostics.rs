@@ -0,0 +1,139 @@
+//! Runtime inspection of the live rendezvous of a process.
+//!
+//! With the `ops` feature, which enables the [registry](crate::debug) of
+//! the live rendezvous, [`serve`] lets operators query the state of all of
+//! them over a Unix domain socket without attaching a debugger.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! std::thread::spawn(|| rendezvous::diagnostics::serve("/run/my-service/rendezvous.sock"));
+//! ```
+//!
+//! The state can then be queried with e.g. `socat`, sending `text` or `json`
+//! to pick the format:
+//!
+//! ```text
+//! $ echo json | socat - UNIX-CONNECT:/run/my-service/rendezvous.sock
+//! [{"id":"0x5581c0d3e000","name":"stage-3","live":3,"waiting":1,"blocked":1}]
+//! ```
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    time::Duration,
+};
+
+pub use crate::debug::Group;
+
+/// Returns the state of all the live rendezvous of the process, see
+/// [`debug::dump`](crate::debug::dump).
+pub fn groups() -> Vec<Group> {
+    crate::debug::dump()
+}
+
+/// Serves the state of all the live rendezvous on a Unix domain socket
+/// bound at `path`.
+///
+/// Each client sends a line with the requested format, either `text` or
+/// `json`, and receives the state of all the rendezvous before the
+/// connection is closed. An empty request defaults to text.
+///
+/// A client which does not send its request nor read the response within
+/// five seconds is disconnected, so that it cannot hold up the others,
+/// which are served one at a time. Failures to accept a connection are
+/// logged, and the endpoint keeps serving: this only returns on failure to
+/// bind the socket.
+pub fn serve<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let listener = UnixListener::bind(path)?;
+    for stream in listener.incoming() {
+        match stream {
+            // A misbehaving client must not bring the endpoint down.
+            Ok(stream) => {
+                let _ = respond(stream);
+            }
+            Err(error) => warn(&error),
+        }
+    }
+    unreachable!("UnixListener::incoming never returns None")
+}
+
+/// How long [`serve`] waits for each read and write of a client.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn respond(stream: UnixStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+    let response = match request.trim() {
+        "json" => to_json(&groups()),
+        "text" | "" => to_text(&groups()),
+        _ => "unknown format, expected `text` or `json`\n".to_owned(),
+    };
+    (&stream).write_all(response.as_bytes())
+}
+
+#[cold]
+fn warn(error: &io::Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%error, "rendezvous diagnostics failed to accept a connection");
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("rendezvous diagnostics failed to accept a connection: {error}");
+}
+
+fn to_text(groups: &[Group]) -> String {
+    let mut out = String::new();
+    for group in groups {
+        let _ = write!(out, "{:#x}", group.id);
+        if let Some(name) = &group.name {
+            let _ = write!(out, " ({name})");
+        }
+        let _ = writeln!(
+            out,
+            ": {} live, {} waiting ({} blocked)",
+            group.live, group.waiting, group.blocked
+        );
+    }
+    out
+}
+
+fn to_json(groups: &[Group]) -> String {
+    let mut out = String::from("[");
+    for (i, group) in groups.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, r#"{{"id":"{:#x}","#, group.id);
+        if let Some(name) = &group.name {
+            out.push_str(r#""name":"#);
+            push_json_string(&mut out, name);
+            out.push(',');
+        }
+        let _ = write!(
+            out,
+            r#""live":{},"waiting":{},"blocked":{}}}"#,
+            group.live, group.waiting, group.blocked
+        );
+    }
+    out.push_str("]\n");
+    out
+}
+
+/// Appends `s` to `out` as a JSON string.
+fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            c if c.is_control() => {
+                let _ = write!(out, r"\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
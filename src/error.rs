@@ -0,0 +1,224 @@
+//! Errors of the fallible waits and operations.
+//!
+//! Fallible waits, such as [`Rendezvous::wait_timeout`], return a
+//! [`WaitOutcome`] when they do not complete normally, which tells why and
+//! gives the reference back when it can still be used.
+
+use core::{error::Error, fmt};
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+use crate::Exchanger;
+use crate::Rendezvous;
+
+/// Why a fallible wait did not complete normally.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WaitOutcome {
+    /// Some references were still live when the wait timed out.
+    TimedOut(WaitTimeout),
+    /// A participant panicked before arriving.
+    Poisoned(Poisoned),
+    /// The wait was given up before completion.
+    Aborted(Aborted),
+}
+
+impl WaitOutcome {
+    /// Returns the reference handed back by the failed wait, if any.
+    pub fn into_rendezvous(self) -> Option<Rendezvous> {
+        match self {
+            Self::TimedOut(timeout) => Some(timeout.into_inner()),
+            Self::Poisoned(_) | Self::Aborted(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for WaitOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut(e) => e.fmt(f),
+            Self::Poisoned(e) => e.fmt(f),
+            Self::Aborted(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for WaitOutcome {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::TimedOut(e) => Some(e),
+            Self::Poisoned(e) => Some(e),
+            Self::Aborted(e) => Some(e),
+        }
+    }
+}
+
+impl From<WaitTimeout> for WaitOutcome {
+    fn from(e: WaitTimeout) -> Self {
+        Self::TimedOut(e)
+    }
+}
+
+impl From<Poisoned> for WaitOutcome {
+    fn from(e: Poisoned) -> Self {
+        Self::Poisoned(e)
+    }
+}
+
+impl From<Aborted> for WaitOutcome {
+    fn from(e: Aborted) -> Self {
+        Self::Aborted(e)
+    }
+}
+
+/// Some references were still live when a wait timed out.
+///
+/// The reference which was waiting is live again, exactly as before the
+/// wait, and can be recovered with [`WaitTimeout::into_inner`].
+#[derive(Debug)]
+pub struct WaitTimeout {
+    rdv: Rendezvous,
+}
+
+impl WaitTimeout {
+    pub(crate) fn new(rdv: Rendezvous) -> Self {
+        Self { rdv }
+    }
+
+    /// Returns the reference which was waiting.
+    pub fn into_inner(self) -> Rendezvous {
+        self.rdv
+    }
+}
+
+impl fmt::Display for WaitTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("timed out waiting for the other references of a rendezvous")
+    }
+}
+
+impl Error for WaitTimeout {}
+
+/// A participant of the rendezvous panicked before arriving.
+///
+/// Only rendezvous created with [`PanicMode::Poison`](crate::PanicMode::Poison)
+/// report it, see [`Rendezvous::with_panic_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Poisoned {
+    pub(crate) panicked: u32,
+}
+
+impl Poisoned {
+    /// Returns the number of participants which panicked while holding a
+    /// reference.
+    pub fn panicked(&self) -> u32 {
+        self.panicked
+    }
+}
+
+impl fmt::Display for Poisoned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.panicked {
+            1 => f.write_str("a participant of the rendezvous panicked"),
+            n => write!(f, "{n} participants of the rendezvous panicked"),
+        }
+    }
+}
+
+impl Error for Poisoned {}
+
+/// A wait was given up before completion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Aborted;
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the wait on the rendezvous was aborted")
+    }
+}
+
+impl Error for Aborted {}
+
+/// A rendezvous cannot have more than 2³² - 1 references, or 2⁶⁴ - 1 with the
+/// `u64-counters` feature, see [`Rendezvous::try_clone`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HandleLimitExceeded;
+
+impl fmt::Display for HandleLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(not(feature = "u64-counters"))]
+        let limit = "2^32 - 1";
+        #[cfg(feature = "u64-counters")]
+        let limit = "2^64 - 1";
+        write!(
+            f,
+            "there cannot be more than {limit} references to a rendezvous"
+        )
+    }
+}
+
+impl Error for HandleLimitExceeded {}
+
+/// Why an exchange did not happen, giving the value back.
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+pub enum ExchangeError<T> {
+    /// The other side was dropped without exchanging.
+    Abandoned(T),
+    /// The other side did not exchange in time. The exchanger can still be
+    /// used.
+    TimedOut(Exchanger<T>, T),
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+impl<T> ExchangeError<T> {
+    /// Returns the value which was not exchanged.
+    pub fn into_value(self) -> T {
+        match self {
+            Self::Abandoned(value) | Self::TimedOut(_, value) => value,
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+impl<T> fmt::Debug for ExchangeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Abandoned(_) => f.write_str("Abandoned(..)"),
+            Self::TimedOut(..) => f.write_str("TimedOut(..)"),
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+impl<T> fmt::Display for ExchangeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Abandoned(_) => f.write_str("the other side of the exchanger was dropped"),
+            Self::TimedOut(..) => {
+                f.write_str("timed out waiting for the other side of the exchanger")
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+impl<T> Error for ExchangeError<T> {}
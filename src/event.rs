@@ -0,0 +1,84 @@
+//! The completion of rendezvous as Windows event objects, for native waits.
+
+use std::{
+    io,
+    os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle},
+    ptr,
+};
+
+use windows_sys::Win32::System::Threading::{CreateEventW, SetEvent};
+
+use crate::{Observer, RDVInner, Rendezvous};
+
+impl RDVInner {
+    fn completion_handle(&self) -> io::Result<OwnedHandle> {
+        // SAFETY: without attributes nor name, CreateEventW has no
+        // precondition. Manual-reset, so that it stays signalled.
+        let handle = unsafe { CreateEventW(ptr::null(), 1, 0, ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the handle was just created, and nobody else owns it.
+        let handle = unsafe { OwnedHandle::from_raw_handle(handle) };
+        // A duplicate, closed once signalled, so that the caller can close
+        // the one returned at any time.
+        let signal = handle.try_clone()?;
+        self.links.push(move || {
+            // SAFETY: signal is an open event handle. Signalling it cannot
+            // fail.
+            unsafe { SetEvent(signal.as_raw_handle()) };
+        });
+        Ok(handle)
+    }
+}
+
+impl Rendezvous {
+    /// Returns a handle to an event object which is signalled once the
+    /// rendezvous completes, for `WaitForMultipleObjects` and the other
+    /// native waits of Windows.
+    ///
+    /// The event is manual-reset: it stays signalled once the rendezvous
+    /// completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::{os::windows::io::AsRawHandle, thread};
+    /// use windows_sys::Win32::{
+    ///     Foundation::WAIT_OBJECT_0,
+    ///     System::Threading::{WaitForSingleObject, INFINITE},
+    /// };
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let completion = rdv.completion_handle()?;
+    /// thread::spawn(move || {
+    ///     // Do some work.
+    ///     drop(rdv);
+    /// });
+    /// // Safety: the handle is open.
+    /// let waited = unsafe { WaitForSingleObject(completion.as_raw_handle(), INFINITE) };
+    /// assert_eq!(waited, WAIT_OBJECT_0);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method is only available on Windows.
+    /// - This reference must still be dropped for the rendezvous to
+    ///   complete, see [`Observer::completion_handle`] to get one without
+    ///   keeping a reference.
+    pub fn completion_handle(&self) -> io::Result<OwnedHandle> {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.completion_handle()
+    }
+}
+
+impl Observer {
+    /// Returns a handle to an event object which is signalled once the
+    /// observed rendezvous completes, see [`Rendezvous::completion_handle`].
+    pub fn completion_handle(&self) -> io::Result<OwnedHandle> {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.completion_handle()
+    }
+}
@@ -0,0 +1,191 @@
+//! The completion of rendezvous as file descriptors, for event loops.
+
+#[cfg(feature = "mio")]
+use std::os::fd::{AsFd, BorrowedFd, RawFd};
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+#[cfg(feature = "mio")]
+use mio::{event::Source, unix::SourceFd, Interest, Registry, Token};
+
+use crate::{Observer, RDVInner, Rendezvous};
+
+impl RDVInner {
+    fn completion_fd(&self) -> io::Result<OwnedFd> {
+        // SAFETY: eventfd has no precondition.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the file descriptor was just opened, and nobody else owns
+        // it.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        // A duplicate, closed once signalled, so that the caller can close
+        // the one returned at any time.
+        let signal = fd.try_clone()?;
+        self.links.push(move || {
+            let one = 1u64.to_ne_bytes();
+            // SAFETY: signal is open, and one is valid for 8 bytes. Adding one
+            // to a fresh counter cannot fail.
+            unsafe { libc::write(signal.as_raw_fd(), one.as_ptr().cast(), one.len()) };
+        });
+        Ok(fd)
+    }
+}
+
+impl Rendezvous {
+    /// Returns an eventfd which becomes readable once the rendezvous
+    /// completes, for event loops built on `epoll` or `poll`.
+    ///
+    /// The eventfd is non-blocking and close-on-exec, and reads 1 once
+    /// readable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::{fs::File, io::Read, thread};
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let mut completion = File::from(rdv.completion_fd()?);
+    /// thread::spawn(move || {
+    ///     // Do some work.
+    ///     drop(rdv);
+    /// });
+    /// // An event loop would poll the file descriptor instead.
+    /// let mut value = [0; 8];
+    /// while completion.read(&mut value).is_err() {
+    ///     thread::yield_now();
+    /// }
+    /// assert_eq!(u64::from_ne_bytes(value), 1);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method is only available on Linux and Android.
+    /// - This reference must still be dropped for the rendezvous to
+    ///   complete, see [`Observer::completion_fd`] to get one without
+    ///   keeping a reference.
+    pub fn completion_fd(&self) -> io::Result<OwnedFd> {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.completion_fd()
+    }
+}
+
+impl Observer {
+    /// Returns an eventfd which becomes readable once the observed
+    /// rendezvous completes, see [`Rendezvous::completion_fd`].
+    pub fn completion_fd(&self) -> io::Result<OwnedFd> {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.completion_fd()
+    }
+}
+
+/// A [mio](https://docs.rs/mio) event source which becomes readable once a
+/// rendezvous completes, created by [`Rendezvous::completion_source`].
+///
+/// It is backed by the eventfd of [`Rendezvous::completion_fd`].
+#[cfg(feature = "mio")]
+#[derive(Debug)]
+pub struct CompletionSource {
+    fd: OwnedFd,
+}
+
+#[cfg(feature = "mio")]
+impl Rendezvous {
+    /// Returns a [`CompletionSource`] of this rendezvous, to register in a
+    /// mio `Poll` alongside sockets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mio::{Events, Interest, Poll, Token};
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let mut poll = Poll::new()?;
+    /// let rdv = Rendezvous::new();
+    /// let mut completion = rdv.completion_source()?;
+    /// poll.registry()
+    ///     .register(&mut completion, Token(0), Interest::READABLE)?;
+    /// thread::spawn(move || {
+    ///     // Do some work.
+    ///     drop(rdv);
+    /// });
+    /// let mut events = Events::with_capacity(8);
+    /// poll.poll(&mut events, None)?;
+    /// assert!(events.iter().any(|event| event.token() == Token(0)));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `mio` feature, and is only available on
+    ///   Linux and Android.
+    /// - This reference must still be dropped for the rendezvous to
+    ///   complete, see [`Observer::completion_source`] to get one without
+    ///   keeping a reference.
+    pub fn completion_source(&self) -> io::Result<CompletionSource> {
+        let fd = self.completion_fd()?;
+        Ok(CompletionSource { fd })
+    }
+}
+
+#[cfg(feature = "mio")]
+impl Observer {
+    /// Returns a [`CompletionSource`] of the observed rendezvous, see
+    /// [`Rendezvous::completion_source`].
+    pub fn completion_source(&self) -> io::Result<CompletionSource> {
+        let fd = self.completion_fd()?;
+        Ok(CompletionSource { fd })
+    }
+}
+
+#[cfg(feature = "mio")]
+impl Source for CompletionSource {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.fd.as_raw_fd()).deregister(registry)
+    }
+}
+
+#[cfg(feature = "mio")]
+impl AsFd for CompletionSource {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+#[cfg(feature = "mio")]
+impl AsRawFd for CompletionSource {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "mio")]
+impl From<CompletionSource> for OwnedFd {
+    fn from(source: CompletionSource) -> Self {
+        source.fd
+    }
+}
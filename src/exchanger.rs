@@ -0,0 +1,148 @@
+//! Two threads swapping values at a rendezvous point.
+
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    mem::ManuallyDrop,
+    ptr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use rendezvous_core::{Futex as _, TimedFutex as _};
+
+use crate::{error::ExchangeError, futex::Futex};
+
+/// One of the two sides of an exchange, created by [`Exchanger::pair`].
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Exchanger;
+/// use std::thread;
+///
+/// let (left, right) = Exchanger::pair();
+/// let other = thread::spawn(move || right.exchange("from the right").unwrap());
+/// assert_eq!(left.exchange("from the left").unwrap(), "from the right");
+/// assert_eq!(other.join().unwrap(), "from the left");
+/// ```
+pub struct Exchanger<T> {
+    inner: Arc<Inner<T>>,
+    side: usize,
+}
+
+struct Inner<T> {
+    // Each side only writes its own value, before setting its bit of the
+    // state, and only reads the other value after seeing its bit.
+    values: [UnsafeCell<Option<T>>; 2],
+    state: AtomicU32,
+}
+
+// Bits of the state, the first two being those of the sides.
+const ABANDONED: u32 = 1 << 2;
+
+// Safety: the values are only accessed as described on Inner::values, which
+// moves them from one thread to the other.
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Exchanger<T> {
+    /// Creates the two sides of an exchange.
+    pub fn pair() -> (Self, Self) {
+        let inner = Arc::new(Inner {
+            values: [UnsafeCell::new(None), UnsafeCell::new(None)],
+            state: AtomicU32::new(0),
+        });
+        (
+            Self {
+                inner: inner.clone(),
+                side: 0,
+            },
+            Self { inner, side: 1 },
+        )
+    }
+
+    /// Blocks until the other side exchanges too, and returns its value.
+    ///
+    /// # Errors
+    ///
+    /// If the other side is dropped without exchanging, giving `value` back.
+    pub fn exchange(self, value: T) -> Result<T, ExchangeError<T>> {
+        self.exchange_until(value, None)
+    }
+
+    /// Same as [`Exchanger::exchange`], but gives up once `timeout` has
+    /// elapsed, giving this side and `value` back.
+    pub fn exchange_timeout(self, value: T, timeout: Duration) -> Result<T, ExchangeError<T>> {
+        self.exchange_until(value, Some(Instant::now() + timeout))
+    }
+
+    fn exchange_until(self, value: T, deadline: Option<Instant>) -> Result<T, ExchangeError<T>> {
+        let this = ManuallyDrop::new(self);
+        let side = this.side;
+        // Safety: this is neither used nor dropped afterwards.
+        let inner = unsafe { ptr::read(&this.inner) };
+        let (mine, theirs) = (1 << side, 1 << (1 - side));
+        // Safety: see Inner::values, we did not set our bit yet.
+        unsafe { *inner.values[side].get() = Some(value) };
+        let mut state = inner.state.fetch_or(mine, Ordering::AcqRel) | mine;
+        loop {
+            if state & theirs != 0 {
+                // The other side will only take our value once woken up.
+                Futex::wake_all(&inner.state);
+                // Safety: see Inner::values, we saw their bit.
+                let value = unsafe { (*inner.values[1 - side].get()).take() };
+                return Ok(value.expect("the other side set its value"));
+            }
+            // Safety: in the cases below, the other side is either gone or
+            // does not know about our value anymore.
+            let take_back = || unsafe { (*inner.values[side].get()).take().unwrap() };
+            if state & ABANDONED != 0 {
+                return Err(ExchangeError::Abandoned(take_back()));
+            }
+            match deadline {
+                None => Futex::wait(&inner.state, state),
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        match inner.state.compare_exchange(
+                            state,
+                            state & !mine,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        ) {
+                            Ok(_) => {
+                                let value = take_back();
+                                let this = Self { inner, side };
+                                return Err(ExchangeError::TimedOut(this, value));
+                            }
+                            Err(actual) => {
+                                state = actual;
+                                continue;
+                            }
+                        }
+                    }
+                    Futex::wait_timeout(&inner.state, state, remaining);
+                }
+            }
+            state = inner.state.load(Ordering::Acquire);
+        }
+    }
+}
+
+impl<T> Drop for Exchanger<T> {
+    fn drop(&mut self) {
+        self.inner.state.fetch_or(ABANDONED, Ordering::Release);
+        Futex::wake_all(&self.inner.state);
+    }
+}
+
+impl<T> fmt::Debug for Exchanger<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Exchanger")
+            .field("side", &self.side)
+            .finish_non_exhaustive()
+    }
+}
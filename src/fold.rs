@@ -0,0 +1,128 @@
+//! A rendezvous folding the values of its participants.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::Rendezvous;
+
+/// A [`Rendezvous`] where each participant contributes a value, and waiters
+/// receive the fold of all of them.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Fold;
+/// use std::thread;
+///
+/// let sum = Fold::new(0, |a, b| a + b);
+/// for i in 1..=4 {
+///     let sum = sum.clone();
+///     thread::spawn(move || sum.finish(i * i));
+/// }
+/// assert_eq!(sum.wait().unwrap(), 30);
+/// ```
+pub struct Fold<T> {
+    rdv: Rendezvous,
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    // None only if a call to combine panicked.
+    acc: Mutex<Option<T>>,
+    combine: Box<dyn Fn(T, T) -> T + Send + Sync>,
+}
+
+impl<T> Fold<T> {
+    /// Creates a new rendezvous, whose values are folded with `combine`,
+    /// starting from `init`.
+    ///
+    /// # Remarks
+    ///
+    /// - Values are folded in the order participants finish, so `combine`
+    ///   should be associative and commutative.
+    pub fn new<F>(init: T, combine: F) -> Self
+    where
+        F: Fn(T, T) -> T + Send + Sync + 'static,
+    {
+        Self::with_rendezvous(Rendezvous::new(), init, combine)
+    }
+
+    /// Creates a new rendezvous on top of `rdv`, e.g. built with a
+    /// [`Builder`](crate::Builder).
+    ///
+    /// `rdv` should not have been cloned, as other references would not
+    /// contribute values.
+    pub fn with_rendezvous<F>(rdv: Rendezvous, init: T, combine: F) -> Self
+    where
+        F: Fn(T, T) -> T + Send + Sync + 'static,
+    {
+        Self {
+            rdv,
+            shared: Arc::new(Shared {
+                acc: Mutex::new(Some(init)),
+                combine: Box::new(combine),
+            }),
+        }
+    }
+
+    /// Folds `value` into the result and drops this reference.
+    pub fn finish(self, value: T) {
+        let mut acc = self
+            .shared
+            .acc
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // If combine panics, acc stays None and the rendezvous records the
+        // panic when self is dropped.
+        if let Some(prev) = acc.take() {
+            *acc = Some((self.shared.combine)(prev, value));
+        }
+    }
+}
+
+impl<T: Clone> Fold<T> {
+    /// Drops this reference, waits until all other references are dropped,
+    /// and returns the fold of the values they finished with.
+    ///
+    /// The references dropped without calling [`finish`](Fold::finish) do
+    /// not contribute a value.
+    ///
+    /// # Errors
+    ///
+    /// If the rendezvous is poisoned, or if a call to the combine function
+    /// panicked, whatever the [`PanicMode`](crate::PanicMode).
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait(self) -> Result<T, crate::error::Poisoned> {
+        let Self { rdv, shared } = self;
+        let result = rdv.wait()?;
+        // All other references arrived, so the value is final.
+        let acc = shared.acc.lock().unwrap_or_else(PoisonError::into_inner);
+        acc.clone().ok_or(crate::error::Poisoned {
+            panicked: result.panicked(),
+        })
+    }
+}
+
+impl<T> Clone for Fold<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rdv: self.rdv.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Fold<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fold")
+            .field("rdv", &self.rdv)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,729 @@
+//! Blocking primitives on an [`AtomicU32`], with timeouts and targeted
+//! wakes, on top of the native futex-like API of each platform.
+//!
+//! | Platform        | API                                    |
+//! |-----------------|----------------------------------------|
+//! | Linux, Android  | `futex`                                |
+//! | Windows         | `WaitOnAddress`                        |
+//! | macOS, iOS      | `__ulock_wait`                         |
+//! | FreeBSD         | `_umtx_op`                             |
+//! | Fuchsia         | `zx_futex_wait`                        |
+//! | ESP-IDF         | FreeRTOS semaphores, one per thread    |
+//! | WebAssembly     | `memory.atomic.wait32`, or spinning    |
+//! | Others          | Condition variables, shared by address |
+//! | Without `std`   | The backend set with [`set_backend`]   |
+//! | With `spin`     | Spinning                               |
+//!
+//! Under `cfg(shuttle)`, they are all replaced by yields to the shuttle
+//! scheduler, which runs its threads one at a time and would hang on a
+//! blocking system call.
+//!
+//! [`set_backend`]: crate::set_backend
+
+use core::sync::atomic::AtomicU32;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// The [`Futex`](rendezvous_core::Futex) implementation of the platform.
+pub(crate) struct Futex;
+
+impl rendezvous_core::Futex for Futex {
+    fn wait(atomic: &AtomicU32, value: u32) {
+        crate::hooks::futex_waiting();
+        #[cfg(feature = "std")]
+        imp::wait(atomic, value, None);
+        #[cfg(all(not(feature = "std"), not(feature = "spin")))]
+        crate::backend::wait(atomic, value);
+        #[cfg(all(not(feature = "std"), feature = "spin"))]
+        spin::wait(atomic, value);
+    }
+
+    fn wake_all(atomic: *const AtomicU32) {
+        crate::hooks::futex_woken();
+        #[cfg(feature = "std")]
+        imp::wake_all(atomic);
+        #[cfg(all(not(feature = "std"), not(feature = "spin")))]
+        crate::backend::wake_all(atomic);
+        #[cfg(all(not(feature = "std"), feature = "spin"))]
+        let _ = atomic;
+    }
+
+    #[cfg(all(
+        feature = "std",
+        any(target_os = "linux", target_os = "android"),
+        not(shuttle)
+    ))]
+    fn wait_class(atomic: &AtomicU32, value: u32, classes: u32) {
+        crate::hooks::futex_waiting();
+        imp::wait_bitset(atomic, value, classes);
+    }
+
+    #[cfg(all(
+        feature = "std",
+        any(target_os = "linux", target_os = "android"),
+        not(shuttle)
+    ))]
+    fn wake_class(atomic: *const AtomicU32, classes: u32) {
+        crate::hooks::futex_woken();
+        imp::wake_bitset(atomic, classes);
+    }
+}
+
+#[cfg(feature = "std")]
+impl rendezvous_core::TimedFutex for Futex {
+    fn wait_timeout(atomic: &AtomicU32, value: u32, timeout: Duration) {
+        // Native timeouts are measured on a clock which may stop while the
+        // system is suspended, whatever the clock of the caller. Waking up at
+        // least this often lets the caller re-check its deadline on its own
+        // clock.
+        const MAX_SLICE: Duration = Duration::from_secs(1);
+        crate::hooks::futex_waiting();
+        imp::wait(atomic, value, Some(timeout.min(MAX_SLICE)));
+    }
+}
+
+/// If the value of each of the `atomics` is the one given alongside, blocks
+/// until one of them is woken up. Returns `false` without blocking if the
+/// platform cannot wait on several atomics at once.
+///
+/// Only Linux can, with `futex_waitv` since 5.16, for up to 128 atomics.
+#[cfg(feature = "std")]
+pub(crate) fn wait_any(atomics: &[(&AtomicU32, u32)]) -> bool {
+    #[cfg(all(any(target_os = "linux", target_os = "android"), not(shuttle)))]
+    return imp::wait_any(atomics);
+    #[cfg(not(all(any(target_os = "linux", target_os = "android"), not(shuttle))))]
+    {
+        let _ = atomics;
+        false
+    }
+}
+
+/// Wakes up one of the threads blocked on `atomic`.
+///
+/// It is okay for the pointer to dangle.
+#[cfg(feature = "std")]
+pub(crate) fn wake_one(atomic: *const AtomicU32) {
+    crate::hooks::futex_woken();
+    imp::wake_one(atomic);
+}
+
+/// Spinning, without `std`, for environments with no way to park at all.
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+mod spin {
+    use core::{
+        hint,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    /// How many times a wait checks the value before returning, the callers
+    /// then checking it again.
+    const SPINS: u32 = 1 << 10;
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32) {
+        for _ in 0..SPINS {
+            if atomic.load(Ordering::Relaxed) != value {
+                return;
+            }
+            hint::spin_loop();
+        }
+    }
+}
+
+#[cfg(all(feature = "std", shuttle))]
+mod imp {
+    use std::{sync::atomic::AtomicU32, time::Duration};
+
+    pub(super) fn wait(_atomic: &AtomicU32, _value: u32, _timeout: Option<Duration>) {
+        // Returning spuriously is allowed, the callers check again once the
+        // other threads had a chance to run.
+        shuttle::thread::yield_now();
+    }
+
+    pub(super) fn wake_one(_atomic: *const AtomicU32) {
+        // Waiters yield.
+    }
+
+    pub(super) fn wake_all(_atomic: *const AtomicU32) {
+        // Waiters yield.
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(target_os = "linux", target_os = "android"),
+    not(shuttle)
+))]
+mod imp {
+    use std::{io, ptr, sync::atomic::AtomicU32, time::Duration};
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        let ts = timeout.map(|timeout| libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        });
+        // SAFETY: atomic is a valid, aligned u32 and ts, if any, a valid
+        // timespec for the whole call.
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                atomic as *const AtomicU32,
+                libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+                value,
+                ts.as_ref()
+                    .map_or(ptr::null(), |ts| ts as *const libc::timespec),
+            )
+        };
+    }
+
+    fn wake(atomic: *const AtomicU32, n: libc::c_int) {
+        // SAFETY: FUTEX_WAKE only uses the address as a key, it may dangle.
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                atomic,
+                libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                n,
+            )
+        };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        wake(atomic, 1);
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        wake(atomic, libc::c_int::MAX);
+    }
+
+    /// Waits like [`wait`], without timeout, only woken up by the wakes
+    /// sharing a bit with `bitset`, which must not be zero.
+    pub(super) fn wait_bitset(atomic: &AtomicU32, value: u32, bitset: u32) {
+        // SAFETY: atomic is a valid, aligned u32 for the whole call, and the
+        // null timeout means none.
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                atomic as *const AtomicU32,
+                libc::FUTEX_WAIT_BITSET | libc::FUTEX_PRIVATE_FLAG,
+                value,
+                ptr::null::<libc::timespec>(),
+                ptr::null::<u32>(),
+                bitset,
+            )
+        };
+    }
+
+    /// Wakes up all the threads blocked on `atomic` in [`wait`], or in
+    /// [`wait_bitset`] with a bitset sharing a bit with `bitset`.
+    pub(super) fn wake_bitset(atomic: *const AtomicU32, bitset: u32) {
+        // SAFETY: FUTEX_WAKE_BITSET only uses the address as a key, it may
+        // dangle.
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                atomic,
+                libc::FUTEX_WAKE_BITSET | libc::FUTEX_PRIVATE_FLAG,
+                libc::c_int::MAX,
+                ptr::null::<libc::timespec>(),
+                ptr::null::<u32>(),
+                bitset,
+            )
+        };
+    }
+
+    /// struct futex_waitv, from linux/futex.h.
+    #[repr(C)]
+    struct FutexWaitv {
+        val: u64,
+        uaddr: u64,
+        flags: u32,
+        reserved: u32,
+    }
+
+    const FUTEX2_SIZE_U32: u32 = 0x02;
+    const FUTEX_WAITV_MAX: usize = 128;
+
+    pub(super) fn wait_any(atomics: &[(&AtomicU32, u32)]) -> bool {
+        if atomics.len() > FUTEX_WAITV_MAX {
+            return false;
+        }
+        let waiters: Vec<_> = atomics
+            .iter()
+            .map(|&(atomic, value)| FutexWaitv {
+                val: value.into(),
+                uaddr: atomic as *const AtomicU32 as u64,
+                // Private, to match the wakes.
+                flags: FUTEX2_SIZE_U32 | libc::FUTEX_PRIVATE_FLAG as u32,
+                reserved: 0,
+            })
+            .collect();
+        // SAFETY: waiters is a valid array of futex_waitv for the whole call,
+        // pointing to valid, aligned u32.
+        let r = unsafe {
+            libc::syscall(
+                libc::SYS_futex_waitv,
+                waiters.as_ptr(),
+                waiters.len() as libc::c_uint,
+                0,
+                ptr::null::<libc::timespec>(),
+                libc::CLOCK_MONOTONIC,
+            )
+        };
+        r >= 0 || io::Error::last_os_error().raw_os_error() != Some(libc::ENOSYS)
+    }
+}
+
+#[cfg(all(feature = "std", windows, not(shuttle)))]
+mod imp {
+    use std::{sync::atomic::AtomicU32, time::Duration};
+
+    use windows_sys::Win32::System::Threading::{
+        WaitOnAddress, WakeByAddressAll, WakeByAddressSingle, INFINITE,
+    };
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        // Rounded up, so that short timeouts do not turn into busy loops.
+        let ms = timeout.map_or(INFINITE, |timeout| {
+            u32::try_from(timeout.as_nanos().div_ceil(1_000_000))
+                .map_or(INFINITE - 1, |ms| ms.min(INFINITE - 1))
+        });
+        let atomic: *const AtomicU32 = atomic;
+        let value: *const u32 = &value;
+        // SAFETY: both pointers are valid, aligned u32 for the whole call.
+        unsafe { WaitOnAddress(atomic.cast(), value.cast(), 4, ms) };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        // SAFETY: WakeByAddressSingle only uses the address as a key, it may
+        // dangle.
+        unsafe { WakeByAddressSingle(atomic.cast()) };
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        // SAFETY: WakeByAddressAll only uses the address as a key, it may
+        // dangle.
+        unsafe { WakeByAddressAll(atomic.cast()) };
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    any(target_os = "macos", target_os = "ios"),
+    not(shuttle)
+))]
+mod imp {
+    use std::{ffi::c_void, sync::atomic::AtomicU32, time::Duration};
+
+    // Not public API, but a stable part of the ABI of libSystem, on which
+    // libc++ builds its own atomic waits.
+    extern "C" {
+        fn __ulock_wait(operation: u32, addr: *mut c_void, value: u64, timeout_us: u32) -> i32;
+        fn __ulock_wake(operation: u32, addr: *mut c_void, wake_value: u64) -> i32;
+    }
+
+    const UL_COMPARE_AND_WAIT: u32 = 1;
+    const ULF_WAKE_ALL: u32 = 0x0000_0100;
+    const ULF_NO_ERRNO: u32 = 0x0100_0000;
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        // Zero means no timeout, so actual timeouts are rounded up.
+        let us = timeout.map_or(0, |timeout| {
+            u32::try_from(timeout.as_nanos().div_ceil(1_000)).map_or(u32::MAX, |us| us.max(1))
+        });
+        let atomic: *const AtomicU32 = atomic;
+        // SAFETY: atomic is a valid, aligned u32 for the whole call.
+        unsafe {
+            __ulock_wait(
+                UL_COMPARE_AND_WAIT | ULF_NO_ERRNO,
+                atomic as *mut c_void,
+                value.into(),
+                us,
+            )
+        };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        // SAFETY: __ulock_wake only uses the address as a key, it may dangle.
+        unsafe { __ulock_wake(UL_COMPARE_AND_WAIT | ULF_NO_ERRNO, atomic as *mut c_void, 0) };
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        // SAFETY: __ulock_wake only uses the address as a key, it may dangle.
+        unsafe {
+            __ulock_wake(
+                UL_COMPARE_AND_WAIT | ULF_WAKE_ALL | ULF_NO_ERRNO,
+                atomic as *mut c_void,
+                0,
+            )
+        };
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "freebsd", not(shuttle)))]
+mod imp {
+    use std::{ffi::c_void, mem::size_of, ptr, sync::atomic::AtomicU32, time::Duration};
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        // Relative timeout, measured on CLOCK_MONOTONIC.
+        let mut time = timeout.map(|timeout| libc::_umtx_time {
+            _timeout: libc::timespec {
+                tv_sec: timeout.as_secs() as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as libc::c_long,
+            },
+            _flags: 0,
+            _clockid: libc::CLOCK_MONOTONIC as u32,
+        });
+        let (size, time) = match &mut time {
+            Some(time) => (
+                size_of::<libc::_umtx_time>() as *mut c_void,
+                time as *mut libc::_umtx_time as *mut c_void,
+            ),
+            None => (ptr::null_mut(), ptr::null_mut()),
+        };
+        // SAFETY: atomic is a valid, aligned u32 and time, if any, a valid
+        // _umtx_time of the given size for the whole call.
+        unsafe {
+            libc::_umtx_op(
+                atomic as *const AtomicU32 as *mut c_void,
+                libc::UMTX_OP_WAIT_UINT_PRIVATE,
+                value.into(),
+                size,
+                time,
+            )
+        };
+    }
+
+    fn wake(atomic: *const AtomicU32, n: libc::c_ulong) {
+        // SAFETY: UMTX_OP_WAKE_PRIVATE only uses the address as a key, it may
+        // dangle.
+        unsafe {
+            libc::_umtx_op(
+                atomic as *mut c_void,
+                libc::UMTX_OP_WAKE_PRIVATE,
+                n,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        wake(atomic, 1);
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        wake(atomic, libc::c_int::MAX as libc::c_ulong);
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "fuchsia", not(shuttle)))]
+mod imp {
+    use std::{sync::atomic::AtomicU32, time::Duration};
+
+    // From zircon/types.h.
+    type ZxStatus = i32;
+    type ZxHandle = u32;
+    type ZxTime = i64;
+
+    const ZX_HANDLE_INVALID: ZxHandle = 0;
+    const ZX_TIME_INFINITE: ZxTime = i64::MAX;
+
+    #[link(name = "zircon")]
+    extern "C" {
+        fn zx_clock_get_monotonic() -> ZxTime;
+        fn zx_futex_wait(
+            value_ptr: *const AtomicU32,
+            current_value: u32,
+            new_futex_owner: ZxHandle,
+            deadline: ZxTime,
+        ) -> ZxStatus;
+        fn zx_futex_wake(value_ptr: *const AtomicU32, wake_count: u32) -> ZxStatus;
+    }
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        // Absolute, on the monotonic clock.
+        let deadline = timeout.map_or(ZX_TIME_INFINITE, |timeout| {
+            let timeout = i64::try_from(timeout.as_nanos()).unwrap_or(ZX_TIME_INFINITE);
+            // SAFETY: zx_clock_get_monotonic has no precondition.
+            unsafe { zx_clock_get_monotonic() }.saturating_add(timeout)
+        });
+        // SAFETY: atomic is a valid, aligned u32 for the whole call. Without
+        // a new owner, the futex is not used for priority inheritance.
+        unsafe { zx_futex_wait(atomic, value, ZX_HANDLE_INVALID, deadline) };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        // SAFETY: zx_futex_wake only uses the address as a key, it may
+        // dangle.
+        unsafe { zx_futex_wake(atomic, 1) };
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        // SAFETY: zx_futex_wake only uses the address as a key, it may
+        // dangle.
+        unsafe { zx_futex_wake(atomic, u32::MAX) };
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "espidf", not(shuttle)))]
+mod imp {
+    use std::{
+        ptr,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Mutex, PoisonError,
+        },
+        time::Duration,
+    };
+
+    use esp_idf_sys::{
+        vQueueDelete, xQueueGenericCreate, xQueueGenericSend, xQueueSemaphoreTake, BaseType_t,
+        QueueHandle_t, TickType_t, CONFIG_FREERTOS_HZ,
+    };
+
+    // From freertos/queue.h and freertos/portmacro.h.
+    const QUEUE_TYPE_BINARY_SEMAPHORE: u8 = 3;
+    const SEND_TO_BACK: BaseType_t = 0;
+    const MAX_DELAY: TickType_t = TickType_t::MAX;
+
+    /// A binary semaphore, which the thread owning it takes to block until
+    /// another one gives it.
+    struct Semaphore(QueueHandle_t);
+
+    impl Semaphore {
+        fn new() -> Self {
+            // SAFETY: creating a queue has no precondition, the null handle
+            // returned on failure is checked.
+            let handle = unsafe { xQueueGenericCreate(1, 0, QUEUE_TYPE_BINARY_SEMAPHORE) };
+            assert!(
+                !handle.is_null(),
+                "Failed to allocate a FreeRTOS semaphore."
+            );
+            Self(handle)
+        }
+    }
+
+    impl Drop for Semaphore {
+        fn drop(&mut self) {
+            // SAFETY: waiters remove their semaphore from the table before
+            // returning, so that nobody else holds it anymore.
+            unsafe { vQueueDelete(self.0) };
+        }
+    }
+
+    thread_local! {
+        static SEMAPHORE: Semaphore = Semaphore::new();
+    }
+
+    /// A thread blocked on the atomic at `addr`.
+    struct Waiter {
+        addr: usize,
+        semaphore: QueueHandle_t,
+    }
+
+    // SAFETY: FreeRTOS semaphores can be given from any task.
+    unsafe impl Send for Waiter {}
+
+    /// A power of two, sharing buckets between unrelated atomics only makes
+    /// for longer scans.
+    const BUCKETS: usize = 64;
+
+    static TABLE: [Mutex<Vec<Waiter>>; BUCKETS] = [const { Mutex::new(Vec::new()) }; BUCKETS];
+
+    fn bucket(addr: usize) -> &'static Mutex<Vec<Waiter>> {
+        // The low bits are the same for all aligned atomics.
+        let addr = addr >> 2;
+        &TABLE[(addr ^ addr >> 6) % BUCKETS]
+    }
+
+    fn ticks(timeout: Duration) -> TickType_t {
+        // Rounded up, so that short timeouts do not turn into busy loops.
+        let tick = Duration::from_secs(1) / CONFIG_FREERTOS_HZ;
+        let ticks = timeout.as_nanos().div_ceil(tick.as_nanos());
+        TickType_t::try_from(ticks).map_or(MAX_DELAY - 1, |ticks| ticks.min(MAX_DELAY - 1))
+    }
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        let addr = atomic as *const AtomicU32 as usize;
+        let bucket = bucket(addr);
+        SEMAPHORE.with(|semaphore| {
+            {
+                let mut waiters = bucket.lock().unwrap_or_else(PoisonError::into_inner);
+                // Checked under the lock, which the wakes take after changing
+                // the value, so that they cannot be missed.
+                if atomic.load(Ordering::Relaxed) != value {
+                    return;
+                }
+                waiters.push(Waiter {
+                    addr,
+                    semaphore: semaphore.0,
+                });
+            }
+            // A semaphore given after a previous wait timed out makes this
+            // one return right away, which is allowed: the callers check the
+            // value again.
+            // SAFETY: the semaphore is alive until the thread exits.
+            unsafe { xQueueSemaphoreTake(semaphore.0, timeout.map_or(MAX_DELAY, ticks)) };
+            // Still there if the wait timed out.
+            bucket
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|waiter| waiter.semaphore != semaphore.0);
+        });
+    }
+
+    fn wake(atomic: *const AtomicU32, mut n: usize) {
+        let addr = atomic as usize;
+        bucket(addr)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|waiter| {
+                if waiter.addr != addr || n == 0 {
+                    return true;
+                }
+                n -= 1;
+                // SAFETY: the semaphore is alive as long as its waiter is in
+                // the table, which it does not leave without the lock.
+                unsafe { xQueueGenericSend(waiter.semaphore, ptr::null(), 0, SEND_TO_BACK) };
+                false
+            });
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        wake(atomic, 1);
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        wake(atomic, usize::MAX);
+    }
+}
+
+#[cfg(all(feature = "std", target_arch = "wasm32", not(shuttle)))]
+mod imp {
+    use std::{arch::wasm32, hint, sync::atomic::AtomicU32, time::Duration};
+
+    /// How many times the threads which may not block check the value before
+    /// returning, the callers then checking it again.
+    const SPINS: u32 = 1 << 10;
+
+    /// Whether the current thread may block, which the main thread of a
+    /// browser may not: `memory.atomic.wait32` traps there.
+    fn may_block() -> bool {
+        #[cfg(target_os = "unknown")]
+        {
+            thread_local! {
+                static MAY_BLOCK: bool =
+                    !js_sys::Reflect::has(&js_sys::global(), &"Window".into()).unwrap_or(false);
+            }
+            MAY_BLOCK.with(|may_block| *may_block)
+        }
+        #[cfg(not(target_os = "unknown"))]
+        true
+    }
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        if !may_block() {
+            for _ in 0..SPINS {
+                if atomic.load(std::sync::atomic::Ordering::Relaxed) != value {
+                    return;
+                }
+                hint::spin_loop();
+            }
+            return;
+        }
+        let ns = timeout.map_or(-1, |timeout| {
+            i64::try_from(timeout.as_nanos()).unwrap_or(i64::MAX)
+        });
+        // SAFETY: atomic is a valid, aligned u32, and this thread may block.
+        unsafe { wasm32::memory_atomic_wait32(atomic.as_ptr().cast(), value as i32, ns) };
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        // SAFETY: memory.atomic.notify only uses the address as a key, it
+        // may dangle, but must stay in bounds of the memory, which it does
+        // as the memory never shrinks.
+        unsafe { wasm32::memory_atomic_notify(atomic as *mut i32, 1) };
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        // SAFETY: memory.atomic.notify only uses the address as a key, it
+        // may dangle, but must stay in bounds of the memory, which it does
+        // as the memory never shrinks.
+        unsafe { wasm32::memory_atomic_notify(atomic as *mut i32, u32::MAX) };
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(any(
+        shuttle,
+        target_os = "linux",
+        target_os = "android",
+        windows,
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "espidf",
+        target_arch = "wasm32"
+    ))
+))]
+mod imp {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Condvar, Mutex, PoisonError,
+        },
+        time::Duration,
+    };
+
+    /// The threads waiting on the atomics whose address hashes to it.
+    struct Bucket {
+        lock: Mutex<()>,
+        woken: Condvar,
+    }
+
+    /// A power of two, sharing buckets between unrelated atomics only makes
+    /// for spurious wake-ups.
+    const BUCKETS: usize = 64;
+
+    static TABLE: [Bucket; BUCKETS] = [const {
+        Bucket {
+            lock: Mutex::new(()),
+            woken: Condvar::new(),
+        }
+    }; BUCKETS];
+
+    fn bucket(atomic: *const AtomicU32) -> &'static Bucket {
+        // The low bits are the same for all aligned atomics.
+        let addr = atomic as usize >> 2;
+        &TABLE[(addr ^ addr >> 6) % BUCKETS]
+    }
+
+    pub(super) fn wait(atomic: &AtomicU32, value: u32, timeout: Option<Duration>) {
+        let bucket = bucket(atomic);
+        let guard = bucket.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        // Checked under the lock, which the wakes take after changing the
+        // value, so that they cannot be missed.
+        if atomic.load(Ordering::Relaxed) != value {
+            return;
+        }
+        // Returning early is allowed, the callers check the value again.
+        match timeout {
+            Some(timeout) => drop(bucket.woken.wait_timeout(guard, timeout)),
+            None => drop(bucket.woken.wait(guard)),
+        }
+    }
+
+    pub(super) fn wake_one(atomic: *const AtomicU32) {
+        // The bucket may be shared with waiters on other atomics, so that
+        // waking up a single one could miss the waiter of this one.
+        wake_all(atomic);
+    }
+
+    pub(super) fn wake_all(atomic: *const AtomicU32) {
+        let bucket = bucket(atomic);
+        drop(bucket.lock.lock().unwrap_or_else(PoisonError::into_inner));
+        bucket.woken.notify_all();
+    }
+}
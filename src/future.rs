@@ -0,0 +1,537 @@
+//! Waiting for a [`Rendezvous`] from async code.
+
+use core::{
+    fmt::Debug,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    ptr::NonNull,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(not(feature = "std"))]
+use rendezvous_core::future::WakerSet;
+
+use crate::{error::Poisoned, release, report, Count, RDVInner, Rendezvous, WaitResult};
+
+/// Future returned by [`Rendezvous::wait_async`](crate::Rendezvous::wait_async),
+/// resolving once all the references to the rendezvous are dropped.
+///
+/// The future is [`Unpin`], and can also be driven without pinning through
+/// [`WaitFuture::poll_wait`]. It can be cloned so that several tasks observe
+/// the same completion, and polled again after completion.
+///
+/// # Remarks
+///
+/// - Each clone takes part in the [leader](WaitResult::is_leader) election
+///   as a separate waiter. A clone of a completed future is never the
+///   leader.
+pub struct WaitFuture {
+    ptr: NonNull<RDVInner>,
+    // Index of our waker in the inner's wakers, once registered.
+    slot: Option<usize>,
+    // What we returned with Poll::Ready, if we did.
+    result: Option<Result<WaitResult, Poisoned>>,
+}
+
+impl WaitFuture {
+    /// # Safety
+    ///
+    /// `ptr` must be valid, and ownership of one of its allocation
+    /// dependencies is transferred to the future.
+    pub(crate) unsafe fn new(ptr: NonNull<RDVInner>) -> Self {
+        Self {
+            ptr,
+            slot: None,
+            result: None,
+        }
+    }
+
+    fn inner(&self) -> &RDVInner {
+        // Safety: we own an allocation dependency so the ptr is valid.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl WaitFuture {
+    /// Polls for the completion of the rendezvous, registering the waker of
+    /// `cx` to be woken up once it completes.
+    ///
+    /// This is the same as [`Future::poll`], without requiring the future to
+    /// be pinned, so that it can be embedded in hand-rolled futures and
+    /// state machines. It can be called again after having returned
+    /// [`Poll::Ready`], and will keep returning it.
+    pub fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<Result<WaitResult, Poisoned>> {
+        if let Some(result) = self.result {
+            return Poll::Ready(report(result, self.inner().counters.panic_mode()));
+        }
+        if self.inner().counters.live() > 0 {
+            self.slot = self.inner().wakers.register(self.slot, cx.waker());
+            // The last reference may have been dropped before we registered.
+            if self.inner().counters.live() > 0 {
+                return Poll::Pending;
+            }
+        }
+        let result = self.inner().result();
+        self.result = Some(result);
+        Poll::Ready(report(result, self.inner().counters.panic_mode()))
+    }
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+impl WaitFuture {
+    /// Returns an `IORING_OP_FUTEX_WAIT` entry completing once the
+    /// rendezvous may be complete, or `None` if it is complete already, for
+    /// io_uring based runtimes to wait without registering a waker.
+    ///
+    /// Whatever the result of the entry, the rendezvous is only complete once
+    /// this returns `None`: [`poll_wait`](WaitFuture::poll_wait) then
+    /// returns [`Poll::Ready`] without registering the waker it is given.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use io_uring::IoUring;
+    /// use rendezvous::Rendezvous;
+    /// use std::task::{Context, Waker};
+    ///
+    /// let mut ring = IoUring::new(8)?;
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// std::thread::spawn(move || drop(worker));
+    /// let mut wait = rdv.wait_async();
+    /// while let Some(entry) = wait.futex_wait_entry() {
+    ///     // Safety: the future, which holds the futex, outlives the wait.
+    ///     unsafe { ring.submission().push(&entry).unwrap() };
+    ///     ring.submit_and_wait(1)?;
+    ///     ring.completion().for_each(drop);
+    /// }
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// assert!(wait.poll_wait(&mut cx).is_ready());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `io-uring` feature, and the entry Linux
+    ///   6.7 or later: it completes right away with `-EINVAL` on older
+    ///   kernels, which makes the loop above spin.
+    /// - The futex is in the allocation of the rendezvous, which the future
+    ///   keeps alive: it must not be dropped before the entry completes.
+    pub fn futex_wait_entry(&self) -> Option<io_uring::squeue::Entry> {
+        // From linux/futex.h.
+        const FUTEX2_SIZE_U32: u32 = 0x02;
+        const FUTEX2_PRIVATE: u32 = 128;
+        const FUTEX_BITSET_MATCH_ANY: u64 = u32::MAX as u64;
+
+        let word = self.inner().counters.futex_word();
+        if word.live == 0 {
+            return None;
+        }
+        let entry = io_uring::opcode::FutexWait::new(
+            word.atomic.as_ptr(),
+            word.value.into(),
+            FUTEX_BITSET_MATCH_ANY,
+            // Private, to match the wakes.
+            FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+        );
+        Some(entry.build())
+    }
+}
+
+impl Future for WaitFuture {
+    type Output = Result<WaitResult, Poisoned>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_wait(cx)
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl futures_core::FusedFuture for WaitFuture {
+    fn is_terminated(&self) -> bool {
+        self.result.is_some()
+    }
+}
+
+impl Clone for WaitFuture {
+    fn clone(&self) -> Self {
+        // The clone does not take part in the rendezvous, it only needs the
+        // allocation.
+        self.inner().retain();
+        Self {
+            ptr: self.ptr,
+            slot: None,
+            result: self
+                .result
+                .map(|result| result.map(|result| WaitResult { leader: false, ..result })),
+        }
+    }
+}
+
+impl Drop for WaitFuture {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.inner().wakers.unregister(slot);
+        }
+        // Safety: we own an allocation dependency, which we give up.
+        unsafe { release(self.ptr) };
+    }
+}
+
+// Safety: it is send by design, as is Rendezvous.
+unsafe impl Send for WaitFuture {}
+// Safety: no method taking self by reference changes the allocation.
+unsafe impl Sync for WaitFuture {}
+
+impl Debug for WaitFuture {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WaitFuture")
+            .field("live barriers", &self.inner().counters.live())
+            .finish()
+    }
+}
+
+impl Rendezvous {
+    /// Wraps `future` so that it holds a clone of this rendezvous until it
+    /// completes or is dropped.
+    ///
+    /// Together with [`wait_async`](Rendezvous::wait_async), this lets a
+    /// rendezvous track a group of async tasks, each of them counting as a
+    /// participant for as long as it runs.
+    pub fn track<F: Future>(&self, future: F) -> Tracked<F> {
+        Tracked {
+            future,
+            handle: Some(self.clone()),
+        }
+    }
+
+    /// Spawns `future` on the current tokio runtime, tracked by a clone of
+    /// this rendezvous.
+    ///
+    /// This mirrors `tokio_util::task::TaskTracker`: spawn tasks through a
+    /// rendezvous, then [`wait_async`](Rendezvous::wait_async) on it to close
+    /// the group and wait for all the tasks to complete.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let tracker = Rendezvous::new();
+    /// for i in 0..10 {
+    ///     tracker.spawn_task(async move {
+    ///         println!("Task {i} is running!");
+    ///     });
+    /// }
+    /// // Once the spawned tasks are done.
+    /// tracker.wait_async().await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If called outside of a tokio runtime, see [`tokio::spawn`].
+    #[cfg(feature = "tokio")]
+    pub fn spawn_task<F>(&self, future: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(self.track(future))
+    }
+}
+
+/// Awaiting a rendezvous is the same as awaiting
+/// [`wait_async`](Rendezvous::wait_async).
+///
+/// ```
+/// # async fn example() {
+/// let rdv = rendezvous::Rendezvous::new();
+/// rdv.await;
+/// # }
+/// ```
+impl IntoFuture for Rendezvous {
+    type Output = Result<WaitResult, Poisoned>;
+    type IntoFuture = WaitFuture;
+
+    fn into_future(self) -> WaitFuture {
+        self.wait_async()
+    }
+}
+
+/// Future returned by [`Rendezvous::track`], holding a clone of the
+/// rendezvous until the wrapped future completes.
+#[derive(Debug)]
+pub struct Tracked<F> {
+    future: F,
+    // Dropped as soon as the future completes.
+    handle: Option<Rendezvous>,
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // Safety: the future is structurally pinned, it is never moved out of
+        // self, while the handle is not.
+        let this = unsafe { self.get_unchecked_mut() };
+        // Safety: see above.
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let res = future.poll(cx);
+        if res.is_ready() {
+            this.handle = None;
+        }
+        res
+    }
+}
+
+/// The wakers of the futures waiting on a rendezvous.
+///
+/// Slots are only reused once unregistered, so that a future can always find
+/// its own waker back, even after it was taken by [`Wakers::wake_all`].
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub(crate) struct Wakers {
+    slots: Mutex<Slots>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Slots {
+    wakers: Vec<Option<Waker>>,
+    // The unregistered slots, reused before allocating new ones.
+    free: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl Wakers {
+    /// Registers `waker` in `slot`, allocating a new slot if needed, and
+    /// returns the slot used.
+    fn register(&self, slot: Option<usize>, waker: &Waker) -> Option<usize> {
+        let mut slots = self.slots.lock().unwrap();
+        match slot {
+            Some(i) => {
+                match &mut slots.wakers[i] {
+                    Some(w) if w.will_wake(waker) => {}
+                    w => *w = Some(waker.clone()),
+                }
+                Some(i)
+            }
+            None => match slots.free.pop() {
+                Some(i) => {
+                    slots.wakers[i] = Some(waker.clone());
+                    Some(i)
+                }
+                None => {
+                    slots.wakers.push(Some(waker.clone()));
+                    Some(slots.wakers.len() - 1)
+                }
+            },
+        }
+    }
+
+    fn unregister(&self, slot: usize) {
+        let mut slots = self.slots.lock().unwrap();
+        slots.wakers[slot] = None;
+        slots.free.push(slot);
+    }
+
+    /// Wakes all the registered futures.
+    pub(crate) fn wake_all(&self) {
+        let wakers: Vec<Waker> = self
+            .slots
+            .lock()
+            .unwrap()
+            .wakers
+            .iter_mut()
+            .filter_map(Option::take)
+            .collect();
+        // Woken outside of the lock, as wakers may poll right away.
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// The number of wakers a rendezvous has room for without `std`.
+#[cfg(not(feature = "std"))]
+const WAKER_SLOTS: usize = 4;
+
+/// The wakers of the futures waiting on a rendezvous, in a fixed number of
+/// slots without `std`, so that waking them neither locks nor allocates.
+///
+/// When all the slots are taken, the other futures wake themselves up on
+/// every poll instead, see [`WakerSet`].
+#[cfg(not(feature = "std"))]
+#[derive(Default)]
+pub(crate) struct Wakers {
+    set: WakerSet<WAKER_SLOTS>,
+}
+
+#[cfg(not(feature = "std"))]
+impl Wakers {
+    /// Registers `waker` in `slot`, claiming a free slot if needed, and
+    /// returns the slot used, if any.
+    fn register(&self, slot: Option<usize>, waker: &Waker) -> Option<usize> {
+        self.set.register(slot, waker)
+    }
+
+    fn unregister(&self, slot: usize) {
+        self.set.unregister(slot);
+    }
+
+    /// Wakes all the registered futures.
+    pub(crate) fn wake_all(&self) {
+        self.set.wake_all();
+    }
+}
+
+impl Rendezvous {
+    /// Returns a stream of the number of live references, yielding each
+    /// time it decreases, and ending once it reaches zero.
+    ///
+    /// The stream observes the rendezvous without taking part in it. The
+    /// decreases happening between two polls are reported at once, as the
+    /// latest count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn example() {
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// for i in 0..4 {
+    ///     let rdv = rdv.clone();
+    ///     std::thread::spawn(move || drop(rdv));
+    /// }
+    /// let mut progress = rdv.progress_stream();
+    /// drop(rdv);
+    /// while let Some(remaining) = progress.next().await {
+    ///     println!("{remaining} workers still running");
+    /// }
+    /// # }
+    /// ```
+    pub fn progress_stream(&self) -> Progress {
+        // Safety: self exist so the ptr is valid.
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        inner.watchers.count.fetch_add(1, Ordering::Relaxed);
+        Progress {
+            ptr: self.ptr,
+            slot: None,
+            last: inner.counters.live(),
+        }
+    }
+}
+
+/// Stream returned by [`Rendezvous::progress_stream`], yielding the number
+/// of live references each time it decreases.
+pub struct Progress {
+    ptr: NonNull<RDVInner>,
+    // Index of our waker in the inner's watchers, once registered.
+    slot: Option<usize>,
+    // The live count we last observed.
+    last: Count,
+}
+
+impl Progress {
+    fn inner(&self) -> &RDVInner {
+        // Safety: we own an allocation dependency so the ptr is valid.
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// Returns the live count if it decreased since last observed.
+    fn decreased(&mut self) -> Option<Count> {
+        // Either the arrivals see this stream and its waker, or it sees
+        // them, see Watchers::wake_all.
+        let live = self.inner().counters.live_synchronized();
+        let decreased = live < self.last;
+        self.last = live;
+        decreased.then_some(live)
+    }
+
+    /// Polls for the next decrease of the live count, registering the waker
+    /// of `cx` to be woken up on the next arrival.
+    ///
+    /// This is the same as `Stream::poll_next`, without requiring the
+    /// stream to be pinned.
+    pub fn poll_progress(&mut self, cx: &mut Context<'_>) -> Poll<Option<Count>> {
+        if let Some(live) = self.decreased() {
+            return Poll::Ready(Some(live));
+        }
+        if self.last == 0 {
+            return Poll::Ready(None);
+        }
+        self.slot = self.inner().watchers.wakers.register(self.slot, cx.waker());
+        // Some references may have arrived before we registered.
+        match self.decreased() {
+            Some(live) => Poll::Ready(Some(live)),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Waits for the next decrease of the live count, returning `None` once
+    /// it reached zero.
+    pub async fn next(&mut self) -> Option<Count> {
+        core::future::poll_fn(|cx| self.poll_progress(cx)).await
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl futures_core::Stream for Progress {
+    type Item = Count;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Count>> {
+        self.get_mut().poll_progress(cx)
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let watchers = &self.inner().watchers;
+        if let Some(slot) = self.slot {
+            watchers.wakers.unregister(slot);
+        }
+        watchers.count.fetch_sub(1, Ordering::Relaxed);
+        // Safety: we own an allocation dependency, which we give up.
+        unsafe { release(self.ptr) };
+    }
+}
+
+// Safety: it is send by design, as is Rendezvous.
+unsafe impl Send for Progress {}
+// Safety: no method taking self by reference changes the allocation.
+unsafe impl Sync for Progress {}
+
+impl Debug for Progress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Progress")
+            .field("live barriers", &self.inner().counters.live())
+            .finish()
+    }
+}
+
+/// The wakers of the progress streams of a rendezvous, woken up on each
+/// arrival.
+#[derive(Default)]
+pub(crate) struct Watchers {
+    // Number of streams, so that arrivals only lock the wakers when needed.
+    count: AtomicU32,
+    wakers: Wakers,
+}
+
+impl Watchers {
+    /// Wakes all the registered streams, if any.
+    pub(crate) fn wake_all(&self) {
+        // The arrival calling this just modified live, which orders it with
+        // the read-modify-write of the streams in Progress::decreased.
+        if self.count.load(Ordering::Relaxed) > 0 {
+            self.wakers.wake_all();
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! A gate at which threads block until it is opened, aka. a manual-reset
+//! event.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::Futex;
+
+const CLOSED: u32 = 0;
+const OPEN: u32 = 1;
+
+/// A gate at which threads block until a coordinator opens it, releasing
+/// them all at once.
+///
+/// The gate stays open, letting threads through without blocking, until it
+/// is closed again.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Gate;
+/// use std::thread;
+///
+/// let gate = Gate::new();
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             gate.wait();
+///             // Start at the same time as the others.
+///         });
+///     }
+///     // Prepare the work.
+///     gate.open();
+/// });
+/// ```
+pub struct Gate {
+    state: AtomicU32,
+}
+
+impl Gate {
+    /// Creates a closed gate.
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU32::new(CLOSED),
+        }
+    }
+
+    /// Opens the gate, releasing the threads blocked at it.
+    pub fn open(&self) {
+        if self.state.swap(OPEN, Ordering::Release) == CLOSED {
+            Futex::wake_all(&self.state);
+        }
+    }
+
+    /// Closes the gate, so that threads block at it again.
+    pub fn close(&self) {
+        self.state.store(CLOSED, Ordering::Relaxed);
+    }
+
+    /// Blocks until the gate is open.
+    pub fn wait(&self) {
+        while !self.is_open() {
+            Futex::wait(&self.state, CLOSED);
+        }
+    }
+
+    /// Returns whether the gate is open.
+    pub fn is_open(&self) -> bool {
+        self.state.load(Ordering::Acquire) == OPEN
+    }
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Gate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gate")
+            .field("open", &self.is_open())
+            .finish()
+    }
+}
@@ -0,0 +1,89 @@
+//! Threads joined by a rendezvous, keeping their results.
+
+use std::{fmt, thread};
+
+use crate::Rendezvous;
+
+/// A group of threads tracked by a rendezvous whose results, or panics, are
+/// collected once all of them are done.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::ThreadGroup;
+///
+/// let mut group = ThreadGroup::new();
+/// for i in 0..4 {
+///     group.spawn(move || i * 2);
+/// }
+/// group.spawn(|| panic!("oops"));
+/// let results = group.wait();
+/// assert_eq!(results[2].as_ref().unwrap(), &4);
+/// assert!(results[4].is_err());
+/// ```
+pub struct ThreadGroup<T> {
+    rdv: Rendezvous,
+    handles: Vec<thread::JoinHandle<T>>,
+}
+
+impl<T: Send + 'static> ThreadGroup<T> {
+    /// Creates an empty group.
+    pub fn new() -> Self {
+        Self {
+            rdv: Rendezvous::new(),
+            handles: Vec::new(),
+        }
+    }
+
+    /// Spawns a thread running `f` in the group.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn spawn<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        self.handles.push(self.rdv.spawn(f));
+    }
+
+    /// Returns the rendezvous of the group, which other participants can
+    /// join by cloning it.
+    pub fn rendezvous(&self) -> &Rendezvous {
+        &self.rdv
+    }
+
+    /// Returns the number of threads spawned in the group.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns whether no thread was spawned in the group.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Waits until all the threads of the group, and the other references
+    /// to its rendezvous, are done, and returns the results of the threads
+    /// in the order they were spawned, like [`JoinHandle::join`](thread::JoinHandle::join).
+    pub fn wait(self) -> Vec<thread::Result<T>> {
+        // Panics are reported by the handles.
+        let _ = self.rdv.wait();
+        self.handles
+            .into_iter()
+            .map(thread::JoinHandle::join)
+            .collect()
+    }
+}
+
+impl<T: Send + 'static> Default for ThreadGroup<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ThreadGroup<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadGroup")
+            .field("rdv", &self.rdv)
+            .field("threads", &self.handles.len())
+            .finish()
+    }
+}
@@ -0,0 +1,95 @@
+//! Origins of the live references, for the `debug-handles` feature.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    panic::Location,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{Count, Observer, RDVInner, Rendezvous};
+
+type Origin = &'static Location<'static>;
+
+/// The origins of the live references of a rendezvous, with their count.
+#[derive(Default)]
+pub(crate) struct Handles {
+    origins: Mutex<HashMap<Origin, Count>>,
+}
+
+impl Handles {
+    fn lock(&self) -> MutexGuard<'_, HashMap<Origin, Count>> {
+        // The map is consistent even if a thread panicked.
+        self.origins.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn list(&self) -> Vec<(Origin, Count)> {
+        let mut origins: Vec<_> = self.lock().iter().map(|(&o, &n)| (o, n)).collect();
+        origins.sort_unstable_by_key(|(o, _)| (o.file(), o.line(), o.column()));
+        origins
+    }
+}
+
+fn inner(rdv: &Rendezvous) -> &RDVInner {
+    // Safety: rdv exist so the ptr is valid
+    unsafe { rdv.ptr.as_ref() }
+}
+
+pub(crate) fn created(rdv: &Rendezvous) {
+    *inner(rdv).handles.lock().entry(rdv.origin).or_default() += 1;
+}
+
+pub(crate) fn gone(rdv: &Rendezvous) {
+    if let Entry::Occupied(mut entry) = inner(rdv).handles.lock().entry(rdv.origin) {
+        *entry.get_mut() -= 1;
+        if *entry.get() == 0 {
+            entry.remove();
+        }
+    }
+}
+
+impl Rendezvous {
+    /// Returns where this reference was created.
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `debug-handles` feature.
+    pub fn origin(&self) -> &'static Location<'static> {
+        self.origin
+    }
+
+    /// Returns where the live references of this rendezvous were created,
+    /// with the number of them created at each location, e.g. to find
+    /// which ones were never dropped.
+    ///
+    /// References created by the crate on behalf of a caller, e.g. by
+    /// [`Rendezvous::spawn`], are reported at the location of the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let leaked = rdv.clone();
+    /// let origins = rdv.live_origins();
+    /// assert_eq!(origins.len(), 2);
+    /// assert!(origins.iter().all(|(origin, n)| origin.file().ends_with(".rs") && *n == 1));
+    /// # drop(leaked);
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `debug-handles` feature.
+    pub fn live_origins(&self) -> Vec<(&'static Location<'static>, Count)> {
+        inner(self).handles.list()
+    }
+}
+
+impl Observer {
+    /// Returns where the live references of the observed rendezvous were
+    /// created, see [`Rendezvous::live_origins`].
+    pub fn live_origins(&self) -> Vec<(&'static Location<'static>, Count)> {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.handles.list()
+    }
+}
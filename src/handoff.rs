@@ -0,0 +1,248 @@
+//! A zero-capacity channel, where each send blocks until a receiver takes
+//! the value, like [`sync_channel(0)`](std::sync::mpsc::sync_channel).
+//!
+//! # Examples
+//!
+//! ```
+//! use rendezvous::handoff;
+//! use std::thread;
+//!
+//! let (tx, rx) = handoff::channel();
+//! thread::spawn(move || {
+//!     for i in 0..3 {
+//!         // Returns once the receiver took i.
+//!         tx.send(i).unwrap();
+//!     }
+//! });
+//! assert_eq!(rx.iter().collect::<Vec<_>>(), [0, 1, 2]);
+//! ```
+
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc::{RecvError, SendError},
+        Arc,
+    },
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::Futex;
+
+/// The sending half of a [`channel`], which can be cloned.
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The receiving half of a [`channel`], which can be cloned.
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    // Only accessed by the sender which moved the state to WRITING, or by
+    // the receiver which moved it to READING.
+    slot: UnsafeCell<Option<T>>,
+    // The phase of the handoff in the lowest bits, then the disconnection
+    // flags, so that waiters notice both alike.
+    state: AtomicU32,
+    senders: AtomicU32,
+    receivers: AtomicU32,
+}
+
+// Phases of a handoff.
+const EMPTY: u32 = 0;
+const WRITING: u32 = 1;
+const FULL: u32 = 2;
+const READING: u32 = 3;
+const TAKEN: u32 = 4;
+const PHASE: u32 = 0b111;
+
+/// Set once all the senders are dropped.
+const NO_SENDERS: u32 = 1 << 3;
+/// Set once all the receivers are dropped.
+const NO_RECEIVERS: u32 = 1 << 4;
+
+// Safety: the slot is only accessed as described on Inner::slot, which
+// moves the values from one thread to the other.
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+/// Creates a new zero-capacity channel.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        slot: UnsafeCell::new(None),
+        state: AtomicU32::new(EMPTY),
+        senders: AtomicU32::new(1),
+        receivers: AtomicU32::new(1),
+    });
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+fn with_phase(state: u32, phase: u32) -> u32 {
+    state & !PHASE | phase
+}
+
+impl<T> Inner<T> {
+    /// Moves to `phase` and wakes up the other side.
+    fn set_phase(&self, phase: u32) {
+        self.state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                Some(with_phase(s, phase))
+            })
+            .unwrap();
+        Futex::wake_all(&self.state);
+    }
+
+    /// Tries to move from `state` to `phase`, returning the actual state on
+    /// failure.
+    fn try_phase(&self, state: u32, phase: u32) -> Result<(), u32> {
+        self.state
+            .compare_exchange(
+                state,
+                with_phase(state, phase),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .map(drop)
+    }
+}
+
+impl<T> Sender<T> {
+    /// Blocks until a receiver takes `value`.
+    ///
+    /// # Errors
+    ///
+    /// If all the receivers are dropped before taking the value, giving it
+    /// back.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let inner = &*self.inner;
+        let mut state = inner.state.load(Ordering::Acquire);
+        loop {
+            if state & NO_RECEIVERS != 0 {
+                return Err(SendError(value));
+            }
+            if state & PHASE == EMPTY {
+                match inner.try_phase(state, WRITING) {
+                    Ok(()) => break,
+                    Err(actual) => state = actual,
+                }
+            } else {
+                // Another send is in progress.
+                Futex::wait(&inner.state, state);
+                state = inner.state.load(Ordering::Acquire);
+            }
+        }
+        // Safety: we moved the state to WRITING.
+        unsafe { *inner.slot.get() = Some(value) };
+        inner.set_phase(FULL);
+        loop {
+            let state = inner.state.load(Ordering::Acquire);
+            match state & PHASE {
+                TAKEN => {
+                    inner.set_phase(EMPTY);
+                    return Ok(());
+                }
+                FULL if state & NO_RECEIVERS != 0 => {
+                    if inner.try_phase(state, WRITING).is_ok() {
+                        // Safety: we moved the state to WRITING.
+                        let value = unsafe { (*inner.slot.get()).take() };
+                        inner.set_phase(EMPTY);
+                        return Err(SendError(value.expect("the slot is full")));
+                    }
+                }
+                _ => Futex::wait(&inner.state, state),
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Blocks until a sender hands a value over, and returns it.
+    ///
+    /// # Errors
+    ///
+    /// If all the senders are dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let inner = &*self.inner;
+        let mut state = inner.state.load(Ordering::Acquire);
+        loop {
+            if state & PHASE == FULL {
+                match inner.try_phase(state, READING) {
+                    Ok(()) => {
+                        // Safety: we moved the state to READING.
+                        let value = unsafe { (*inner.slot.get()).take() };
+                        inner.set_phase(TAKEN);
+                        return Ok(value.expect("the slot is full"));
+                    }
+                    Err(actual) => state = actual,
+                }
+            } else if state & NO_SENDERS != 0 {
+                return Err(RecvError);
+            } else {
+                Futex::wait(&inner.state, state);
+                state = inner.state.load(Ordering::Acquire);
+            }
+        }
+    }
+
+    /// Returns an iterator receiving values until all the senders are
+    /// dropped.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.recv().ok())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.inner.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.state.fetch_or(NO_SENDERS, Ordering::Release);
+            Futex::wake_all(&self.inner.state);
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.inner.receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.state.fetch_or(NO_RECEIVERS, Ordering::Release);
+            Futex::wake_all(&self.inner.state);
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.inner.receivers.fetch_add(1, Ordering::Relaxed);
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
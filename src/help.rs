@@ -0,0 +1,83 @@
+//! Waiting from the worker threads of a rayon pool.
+
+use std::time::Duration;
+
+use rendezvous_core::{Futex as _, TimedFutex as _};
+
+use crate::{error::Poisoned, finish, futex::Futex, Rendezvous, WaitResult};
+
+/// Bounds of the blocking between two attempts at helping the pool.
+const MIN_BACKOFF: Duration = Duration::from_micros(10);
+const MAX_BACKOFF: Duration = Duration::from_millis(1);
+
+impl Rendezvous {
+    /// Like [`wait`](Rendezvous::wait), but runs the pending jobs of the
+    /// current rayon pool while waiting.
+    ///
+    /// Blocking a worker thread of a pool in [`wait`](Rendezvous::wait) can
+    /// deadlock it when the other references are held by jobs queued on
+    /// that very thread. Outside of a pool, this is the same as
+    /// [`wait`](Rendezvous::wait).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    /// pool.install(|| {
+    ///     let rdv = Rendezvous::new();
+    ///     for _ in 0..4 {
+    ///         let rdv = rdv.clone();
+    ///         // Queued on the only worker thread, which is waiting.
+    ///         rayon::spawn(move || drop(rdv));
+    ///     }
+    ///     rdv.wait_or_help().unwrap();
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    pub fn wait_or_help(self) -> Result<WaitResult, Poisoned> {
+        let ptr = self.into_ptr();
+        {
+            // Safety: we forgot self so we own its allocation dependency,
+            // until finish below.
+            let inner = unsafe { ptr.as_ref() };
+            let counters = &inner.counters;
+            let mut live = inner.arrive();
+            let mut backoff = MIN_BACKOFF;
+            while live > 0 {
+                match rayon::yield_now() {
+                    Some(rayon::Yield::Executed) => backoff = MIN_BACKOFF,
+                    Some(rayon::Yield::Idle) => {
+                        // New jobs may be queued while blocked, so only
+                        // block for a while.
+                        let _parked = counters.park();
+                        let word = counters.futex_word();
+                        if word.live > 0 {
+                            Futex::wait_timeout(word.atomic, word.value, backoff);
+                        }
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                    // Not in a pool.
+                    None => {
+                        let _parked = counters.park();
+                        let word = counters.futex_word();
+                        if word.live > 0 {
+                            Futex::wait(word.atomic, word.value);
+                        }
+                    }
+                }
+                live = counters.live();
+            }
+            // Only synchronizes, live is 0.
+            counters.wait::<Futex>(live);
+        }
+        // Safety: we own an allocation dependency, which we do not use
+        // afterwards.
+        unsafe { finish(ptr) }
+    }
+}
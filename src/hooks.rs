@@ -0,0 +1,247 @@
+//! Extension points in the life cycle of a rendezvous, for the diagnostic
+//! features.
+//!
+//! Every diagnostic capability plugs in here, behind its own feature, rather
+//! than in the handle methods themselves. With all of them disabled, the
+//! hooks are empty and inlined away, and clone, drop and wait compile to the
+//! same code as if they did not exist. Fields they need in the inner
+//! allocation must be gated the same way, which the layout assertion next to
+//! [`RDVInner`] guards.
+
+use core::ptr::NonNull;
+
+use crate::{Count, RDVInner, Rendezvous};
+
+/// Called once the inner of a new rendezvous is allocated.
+#[inline(always)]
+pub(crate) fn created(ptr: NonNull<RDVInner>) {
+    #[cfg(feature = "registry")]
+    crate::debug::register(ptr);
+    #[cfg(feature = "metrics")]
+    crate::telemetry::created();
+    #[cfg(feature = "stats")]
+    {
+        // Safety: the inner was just allocated.
+        let inner = unsafe { ptr.as_ref() };
+        crate::stats::registered(inner, inner.counters.live());
+    }
+    let _ = ptr;
+}
+
+/// Called once `n` new references are registered on `inner`.
+#[inline(always)]
+pub(crate) fn registered(inner: &RDVInner, n: Count) {
+    #[cfg(feature = "stats")]
+    crate::stats::registered(inner, n);
+    #[cfg(feature = "tracing")]
+    crate::trace::registered(inner, n);
+    let _ = (inner, n);
+}
+
+/// Called once a reference is created, after its registration.
+#[inline(always)]
+pub(crate) fn handle_created(rdv: &Rendezvous) {
+    #[cfg(feature = "debug-handles")]
+    crate::handles::created(rdv);
+    #[cfg(feature = "deadlock-detection")]
+    crate::deadlock::created(rdv);
+    let _ = rdv;
+}
+
+/// Called once a reference stops being live or is handed over to another
+/// kind of handle, before it arrives.
+#[inline(always)]
+pub(crate) fn handle_gone(rdv: &Rendezvous) {
+    #[cfg(feature = "debug-handles")]
+    crate::handles::gone(rdv);
+    #[cfg(feature = "deadlock-detection")]
+    crate::deadlock::gone(rdv);
+    let _ = rdv;
+}
+
+/// Called once a reference is handed over to the links of a rendezvous,
+/// which hold it on behalf of no thread until [`handle_unlinked`].
+#[inline(always)]
+pub(crate) fn handle_linked(rdv: &Rendezvous) {
+    #[cfg(feature = "deadlock-detection")]
+    crate::deadlock::gone(rdv);
+    let _ = rdv;
+}
+
+/// Called once a reference held by links is about to be dropped.
+#[inline(always)]
+pub(crate) fn handle_unlinked(rdv: &Rendezvous) {
+    #[cfg(feature = "deadlock-detection")]
+    crate::deadlock::created(rdv);
+    let _ = rdv;
+}
+
+/// Called once `n` references arrived on `inner`, leaving `live` live ones.
+#[inline(always)]
+pub(crate) fn arrived(inner: &RDVInner, n: Count, live: Count) {
+    #[cfg(feature = "tracing")]
+    crate::trace::arrived(inner, n, live);
+    let _ = (inner, n, live);
+}
+
+/// Held by a blocking wait, from right before its reference arrives until it
+/// returns.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct Waiting {
+    #[cfg(feature = "tracing")]
+    _span: crate::trace::WaitSpan,
+    #[cfg(feature = "metrics")]
+    _timer: crate::telemetry::WaitTimer,
+    #[cfg(feature = "stats")]
+    recorder: crate::stats::WaitRecorder,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl Waiting {
+    /// Called once the wait returned, `live` being the value returned by the
+    /// arrival of the waiting reference.
+    #[inline(always)]
+    pub(crate) fn waited(self, live: Count) -> Waited {
+        let _ = live;
+        Waited {
+            #[cfg(feature = "stats")]
+            stats: self.recorder.finish(live),
+        }
+    }
+}
+
+/// Returned by [`Waiting::waited`], to complete the result of the wait once
+/// the reference is released.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct Waited {
+    #[cfg(feature = "stats")]
+    stats: crate::WaitStats,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl Waited {
+    #[inline(always)]
+    pub(crate) fn complete<E>(
+        self,
+        result: Result<crate::WaitResult, E>,
+    ) -> Result<crate::WaitResult, E> {
+        #[cfg(feature = "stats")]
+        let result = result.map(|result| crate::WaitResult {
+            stats: self.stats,
+            ..result
+        });
+        let _ = self;
+        result
+    }
+}
+
+/// Called right before a reference arrives on `inner` to wait.
+#[inline(always)]
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn waiting(inner: &RDVInner) -> Waiting {
+    let _ = inner;
+    Waiting {
+        #[cfg(feature = "tracing")]
+        _span: crate::trace::WaitSpan::enter(inner),
+        #[cfg(feature = "metrics")]
+        _timer: crate::telemetry::WaitTimer::start(),
+        #[cfg(feature = "stats")]
+        recorder: crate::stats::WaitRecorder::start(),
+    }
+}
+
+/// Called once a reference was moved to the current thread by the crate.
+#[inline(always)]
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+pub(crate) fn handle_moved(rdv: &mut Rendezvous) {
+    #[cfg(feature = "deadlock-detection")]
+    crate::deadlock::moved(rdv);
+    let _ = rdv;
+}
+
+/// Blocks until there is no live handle on `inner` anymore, `live` being the
+/// value returned by the arrival of the waiting reference.
+#[inline(always)]
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn wait(inner: &RDVInner, live: Count) {
+    #[cfg(any(feature = "watchdog", feature = "deadlock-detection"))]
+    monitored_wait(inner, live);
+    #[cfg(not(any(feature = "watchdog", feature = "deadlock-detection")))]
+    inner.counters.wait::<crate::futex::Futex>(live);
+}
+
+/// Blocks like [`wait`], waking up to let the monitors check the wait when
+/// they ask to.
+#[cfg(all(
+    any(feature = "watchdog", feature = "deadlock-detection"),
+    not(all(target_arch = "wasm32", not(target_feature = "atomics")))
+))]
+fn monitored_wait(inner: &RDVInner, mut live: Count) {
+    use crate::futex::Futex;
+
+    let start = std::time::Instant::now();
+    #[cfg(feature = "watchdog")]
+    let mut watchdog = crate::watchdog::Watchdog::new();
+    #[cfg(feature = "deadlock-detection")]
+    let mut detector = crate::deadlock::Detector::new();
+    loop {
+        let checks = [
+            #[cfg(feature = "watchdog")]
+            watchdog.next_check(),
+            #[cfg(feature = "deadlock-detection")]
+            detector.next_check(),
+        ];
+        let Some(next_check) = checks.into_iter().flatten().min() else {
+            return inner.counters.wait::<Futex>(live);
+        };
+        match inner
+            .counters
+            .wait_before::<Futex>(live, next_check, || start.elapsed())
+        {
+            Ok(()) => return,
+            Err(l) => live = l,
+        }
+        let elapsed = start.elapsed();
+        #[cfg(feature = "watchdog")]
+        watchdog.check(inner, live, elapsed);
+        #[cfg(feature = "deadlock-detection")]
+        detector.check(inner, live, elapsed);
+    }
+}
+
+/// Called right before the current thread blocks on a futex.
+#[inline(always)]
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn futex_waiting() {
+    #[cfg(feature = "stats")]
+    crate::stats::futex_waiting();
+}
+
+/// Called right before waking up the threads blocked on a futex.
+#[inline(always)]
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn futex_woken() {
+    #[cfg(feature = "metrics")]
+    crate::telemetry::futex_woken();
+}
+
+/// Runs `reset`, which re-arms the counters of a rendezvous, so that the
+/// diagnostic features see them either before or after.
+#[inline(always)]
+pub(crate) fn resetting(reset: impl FnOnce()) {
+    #[cfg(feature = "registry")]
+    crate::debug::locked(reset);
+    #[cfg(not(feature = "registry"))]
+    reset();
+}
+
+/// Called right before the inner of a rendezvous is freed.
+#[inline(always)]
+pub(crate) fn freeing(ptr: NonNull<RDVInner>) {
+    #[cfg(feature = "registry")]
+    crate::debug::unregister(ptr);
+    let _ = ptr;
+}
@@ -0,0 +1,361 @@
+//! Rendezvous shared by unrelated processes through a well-known file.
+//!
+//! A [`FileRendezvous`] keeps its live count in a small file mapped in memory
+//! by every participating process, so that processes started independently
+//! (cron jobs, CI steps, ...) can synchronize by agreeing on a path, without
+//! setting up shared memory or passing file descriptors around.
+//!
+//! Opening the file registers a new participant, and the usual clone, drop
+//! and wait semantics of [`Rendezvous`](crate::Rendezvous) apply across all
+//! the processes which opened it. Once the count drops to zero, the next
+//! process opening the file starts a new round.
+//!
+//! On Linux and Android, waiters sleep on a process-shared futex. Other
+//! platforms fall back to polling the shared state.
+//!
+//! # Remarks
+//!
+//! - A process exiting without dropping its handles (e.g. because it was
+//!   killed) leaves them registered forever, and waiters will block
+//!   indefinitely. Removing the file resets the rendezvous.
+//! - This module is only available on unix platforms with the `ipc` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rendezvous::ipc::FileRendezvous;
+//!
+//! let rdv = FileRendezvous::open("/tmp/nightly-build.rdv")?;
+//!
+//! // Do some work.
+//!
+//! // Block until all other processes which opened the file are done.
+//! rdv.wait();
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io,
+    mem::size_of,
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::Path,
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Marks files created by this module, "RDV2" in ASCII.
+const MAGIC: u32 = 0x5244_5632;
+
+/// Layout of the mapped file.
+#[repr(C)]
+struct Shared {
+    magic: AtomicU32,
+    // The live count in the low half, and the number of completed rounds,
+    // wrapping around, in the high half, which the waiters block on. Both
+    // change at once when the last handle of a round is dropped, so that a
+    // process opening the file afterwards starts a new round.
+    state: AtomicU64,
+}
+
+const ONE_LIVE: u64 = 1;
+const ONE_ROUND: u64 = 1 << 32;
+
+fn live(state: u64) -> u32 {
+    state as u32
+}
+
+fn round(state: u64) -> u32 {
+    (state >> 32) as u32
+}
+
+const SHARED_LEN: usize = size_of::<Shared>();
+
+/// A rendezvous shared by several processes through a file. See the
+/// [module](self) documentation for more.
+///
+/// # Remarks
+///
+/// - There cannot be more than 2³² - 1 simultaneous handles on a single file,
+///   across all processes.
+pub struct FileRendezvous {
+    mapping: Arc<Mapping>,
+}
+
+/// The shared mapping of the file, common to all the handles of a process.
+struct Mapping {
+    ptr: NonNull<Shared>,
+    // Kept open for the lifetime of the mapping.
+    _file: File,
+}
+
+impl FileRendezvous {
+    /// Opens the rendezvous file at `path`, creating it if it does not exist,
+    /// and registers a new participant.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the file exists but was
+    /// not created by this module.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(path)?;
+        // Serializes the initialization of the file with other processes
+        // opening it at the same time.
+        let lock = FileLock::exclusive(&file)?;
+        let len = file.metadata()?.len();
+        let fresh = len == 0;
+        if fresh {
+            file.set_len(SHARED_LEN as u64)?;
+        } else if len != SHARED_LEN as u64 {
+            return Err(not_a_rendezvous_file());
+        }
+        let mapping = Mapping::new(file)?;
+        let shared = mapping.shared();
+        if fresh {
+            shared.magic.store(MAGIC, Ordering::Release);
+        } else if shared.magic.load(Ordering::Acquire) != MAGIC {
+            return Err(not_a_rendezvous_file());
+        }
+        register(&shared.state);
+        drop(lock);
+        Ok(Self {
+            mapping: Arc::new(mapping),
+        })
+    }
+
+    /// Drops this handle and waits until all other handles, in this and
+    /// other processes, are dropped.
+    ///
+    /// The handles registered once they are all dropped, e.g. by opening the
+    /// file again, take part in the next round and are not waited for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::ipc::FileRendezvous;
+    /// use std::{env, fs, process, thread};
+    ///
+    /// let path = env::temp_dir().join(format!("rendezvous-{}.rdv", process::id()));
+    /// let rdv = FileRendezvous::open(&path)?;
+    /// // Opened by another process in practice.
+    /// let other = FileRendezvous::open(&path)?;
+    /// thread::spawn(move || drop(other));
+    /// rdv.wait();
+    ///
+    /// // Starts a new round.
+    /// let next = FileRendezvous::open(&path)?;
+    /// next.wait();
+    /// fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn wait(self) {
+        let mapping = Arc::clone(&self.mapping);
+        let state = &mapping.shared().state;
+        // Read while this handle keeps the round from completing.
+        let r = round(state.load(Ordering::Acquire));
+        drop(self);
+        let mut backoff = Backoff::new();
+        while round(state.load(Ordering::Acquire)) == r {
+            backoff.wait(round_word(state), r);
+        }
+    }
+}
+
+impl Drop for FileRendezvous {
+    fn drop(&mut self) {
+        let state = &self.mapping.shared().state;
+        let prev = state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                Some(if live(s) == 1 {
+                    (s - ONE_LIVE).wrapping_add(ONE_ROUND)
+                } else {
+                    s - ONE_LIVE
+                })
+            })
+            .unwrap();
+        if live(prev) == 1 {
+            wake_all(round_word(state));
+        }
+    }
+}
+
+impl Clone for FileRendezvous {
+    fn clone(&self) -> Self {
+        register(&self.mapping.shared().state);
+        Self {
+            mapping: Arc::clone(&self.mapping),
+        }
+    }
+}
+
+impl Debug for FileRendezvous {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileRendezvous")
+            .field(
+                "live barriers",
+                &live(self.mapping.shared().state.load(Ordering::Acquire)),
+            )
+            .finish()
+    }
+}
+
+fn register(state: &AtomicU64) {
+    state
+        .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+            (live(s) < u32::MAX).then_some(s + ONE_LIVE)
+        })
+        .expect("There should not be more than 2^32 - 1 handles on one FileRendezvous.");
+}
+
+/// Returns the address of the half of `state` holding the round, for the
+/// futex operations, which are 32 bits wide.
+fn round_word(state: &AtomicU64) -> *const u32 {
+    let halves = state.as_ptr().cast::<u32>();
+    if cfg!(target_endian = "little") {
+        halves.wrapping_add(1)
+    } else {
+        halves
+    }
+}
+
+fn not_a_rendezvous_file() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a rendezvous file")
+}
+
+impl Mapping {
+    fn new(file: File) -> io::Result<Self> {
+        // SAFETY: we map a fresh region, the arguments are checked by the
+        // kernel and the result checked below.
+        let addr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                SHARED_LEN,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            // SAFETY: mmap does not return null on success.
+            ptr: unsafe { NonNull::new_unchecked(addr.cast()) },
+            _file: file,
+        })
+    }
+
+    fn shared(&self) -> &Shared {
+        // SAFETY: the region is mapped, page aligned and at least SHARED_LEN
+        // long until self is dropped. Shared only holds atomics, which
+        // other processes may modify concurrently.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: the region was mapped in Mapping::new and no reference to
+        // it outlives self.
+        unsafe { libc::munmap(self.ptr.as_ptr().cast(), SHARED_LEN) };
+    }
+}
+
+// Safety: the mapping is only accessed through atomics.
+unsafe impl Send for Mapping {}
+// Safety: the mapping is only accessed through atomics.
+unsafe impl Sync for Mapping {}
+
+/// An advisory lock on a whole file, released on drop.
+struct FileLock {
+    // A duplicate of the locked file descriptor, sharing its lock.
+    file: File,
+}
+
+impl FileLock {
+    fn exclusive(file: &File) -> io::Result<Self> {
+        let file = file.try_clone()?;
+        loop {
+            // SAFETY: flock has no memory safety requirements.
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                return Ok(Self { file });
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // SAFETY: flock has no memory safety requirements.
+        unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+struct Backoff;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Backoff {
+    fn new() -> Self {
+        Self
+    }
+
+    fn wait(&mut self, word: *const u32, value: u32) {
+        // Not FUTEX_PRIVATE: waiters and wakers live in different processes.
+        // SAFETY: word is a valid, aligned u32 for the whole call, which the
+        // kernel only reads.
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                word,
+                libc::FUTEX_WAIT,
+                value,
+                ptr::null::<libc::timespec>(),
+            )
+        };
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn wake_all(word: *const u32) {
+    // SAFETY: the kernel only uses the address of word, as a key.
+    unsafe { libc::syscall(libc::SYS_futex, word, libc::FUTEX_WAKE, i32::MAX) };
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+struct Backoff {
+    sleep: std::time::Duration,
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+impl Backoff {
+    const MIN: std::time::Duration = std::time::Duration::from_millis(1);
+    const MAX: std::time::Duration = std::time::Duration::from_millis(100);
+
+    fn new() -> Self {
+        Self { sleep: Self::MIN }
+    }
+
+    fn wait(&mut self, _word: *const u32, _value: u32) {
+        std::thread::sleep(self.sleep);
+        self.sleep = (self.sleep * 2).min(Self::MAX);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn wake_all(_word: *const u32) {
+    // Waiters poll the round.
+}
@@ -0,0 +1,87 @@
+//! Signalling completion from interrupt context.
+
+use core::{fmt::Debug, ptr::NonNull};
+
+use crate::{RDVInner, Rendezvous};
+
+/// A participant of a [`Rendezvous`] which can be dropped from an interrupt
+/// handler.
+///
+/// Dropping an [`IsrToken`] arrives like dropping a [`Rendezvous`] does, but
+/// never frees the shared allocation. It can thus be handed to an interrupt
+/// service routine, e.g. to release a main loop blocked in
+/// [`Rendezvous::wait`] once a DMA transfer completes.
+///
+/// The arrival itself is a bounded number of atomic operations, but the drop
+/// also runs the following in interrupt context:
+///
+/// - waking up the blocked waiters through the platform's wait backend, if
+///   the token is the last participant to arrive or leaves a single one,
+///   which may be waiting in [`wait_ref`](Rendezvous::wait_ref);
+/// - the [`on_progress`](crate::Builder::on_progress) callback of the
+///   rendezvous, if any;
+/// - if the token is the last participant to arrive, the
+///   [`on_complete`](crate::Builder::on_complete) callback and the callbacks
+///   linked to the completion, e.g. by [`Rendezvous::merge`], `wait_all` or
+///   `completion_fd`. They are freed once run, and may drop references to
+///   other rendezvous;
+/// - with the `async` feature, waking up the futures and progress streams,
+///   which, with the `std` feature, locks a standard `Mutex` and collects
+///   their wakers in a `Vec`;
+/// - with the `tracing` feature, the event of the arrival.
+///
+/// Without these features, callbacks and links, and with a wait backend
+/// usable from interrupt context, the drop thus neither allocates, frees
+/// memory nor blocks.
+///
+/// # Remarks
+///
+/// - If the token is dropped after all the handles of its rendezvous, the
+///   shared allocation is leaked instead of being freed from interrupt
+///   context. Keeping a handle until the token is dropped, typically by
+///   waiting on it, avoids the leak.
+pub struct IsrToken {
+    ptr: NonNull<RDVInner>,
+}
+
+impl Rendezvous {
+    /// Registers a new participant, in the form of a token which can be
+    /// dropped from interrupt context.
+    pub fn isr_token(&self) -> IsrToken {
+        // Registering is the same as cloning, only releasing differs.
+        let rdv = self.clone();
+        IsrToken {
+            ptr: rdv.into_ptr(),
+        }
+    }
+}
+
+impl Drop for IsrToken {
+    fn drop(&mut self) {
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.ptr is still valid
+        {
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { self.ptr.as_ref() };
+            inner.arrive();
+        }
+        // Safety: the invariant from the scope above is still true
+        // and is broken in this very instruction.
+        // If we were the last alloc_dependent barrier, nobody can access the
+        // allocation anymore and we leak it rather than freeing it.
+        unsafe { self.ptr.as_ref() }.counters.release();
+    }
+}
+
+// Safety: it is send by design, as is Rendezvous.
+unsafe impl Send for IsrToken {}
+// Safety: IsrToken has no method taking self by reference.
+unsafe impl Sync for IsrToken {}
+
+impl Debug for IsrToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IsrToken").finish_non_exhaustive()
+    }
+}
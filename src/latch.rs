@@ -0,0 +1,80 @@
+//! A latch released once it has been counted down a given number of times.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::Futex;
+
+/// A latch releasing its waiters once [`count_down`](CountdownLatch::count_down)
+/// has been called a given number of times, possibly from a single thread.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::CountdownLatch;
+/// use std::thread;
+///
+/// let latch = CountdownLatch::new(3);
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         for _event in 0..3 {
+///             latch.count_down();
+///         }
+///     });
+///     latch.wait();
+/// });
+/// assert_eq!(latch.count(), 0);
+/// ```
+pub struct CountdownLatch {
+    count: AtomicU32,
+}
+
+impl CountdownLatch {
+    /// Creates a latch released after `count` calls to
+    /// [`count_down`](CountdownLatch::count_down).
+    pub const fn new(count: u32) -> Self {
+        Self {
+            count: AtomicU32::new(count),
+        }
+    }
+
+    /// Decrements the count, releasing the waiters if it reaches zero.
+    ///
+    /// Does nothing if the latch is already released.
+    pub fn count_down(&self) {
+        if let Ok(1) = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |c| c.checked_sub(1))
+        {
+            Futex::wake_all(&self.count);
+        }
+    }
+
+    /// Blocks until the count reaches zero.
+    pub fn wait(&self) {
+        loop {
+            let count = self.count();
+            if count == 0 {
+                return;
+            }
+            Futex::wait(&self.count, count);
+        }
+    }
+
+    /// Returns the current count.
+    pub fn count(&self) -> u32 {
+        self.count.load(Ordering::Acquire)
+    }
+}
+
+impl fmt::Debug for CountdownLatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountdownLatch")
+            .field("count", &self.count())
+            .finish()
+    }
+}
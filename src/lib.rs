@@ -1,4 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::undocumented_unsafe_blocks)]
+#![debugger_visualizer(natvis_file = "../debugger/rendezvous.natvis")]
+#![debugger_visualizer(gdb_script_file = "../debugger/rendezvous_gdb.py")]
 
 //! Enables threads to synchronize the beginning or end of some computation.
 //!
@@ -43,10 +46,77 @@
 //! }
 //!
 //! // Block until all threads have finished their work.
-//! rdv.wait();
+//! rdv.wait().unwrap();
 //! # std::thread::sleep(std::time::Duration::from_millis(500)); // wait for background threads closed: https://github.com/rust-lang/miri/issues/1371
 //! ```
 //!
+//! # Async
+//!
+//! With the `async` feature, [`Rendezvous::wait_async`] returns a future
+//! which does not depend on a specific executor. Threads blocked in
+//! [`Rendezvous::wait`] and tasks awaiting [`Rendezvous::wait_async`] can be
+//! mixed on the same rendezvous: all of them are released when the last live
+//! reference is dropped.
+//!
+//! On Linux, the `io-uring` feature lets io_uring based runtimes wait for
+//! the futures with `IORING_OP_FUTEX_WAIT` entries rather than wakers, see
+//! `WaitFuture::futex_wait_entry`.
+//!
+//! # WebAssembly
+//!
+//! With the `atomics` target feature, e.g. for worker threads in the
+//! browser, the blocking waits go through `memory.atomic.wait32`. The main
+//! thread of a browser is not allowed to block, so that it spins instead,
+//! which is only reasonable for short waits: prefer
+//! [`Rendezvous::wait_async`] there.
+//!
+//! Without the `atomics` target feature, the only thread cannot block at
+//! all. The crate still builds, with [`Rendezvous::wait_async`] always
+//! available, but not the blocking waits, except [`Rendezvous::wait`] on the
+//! last live reference: it panics if other references are live.
+//!
+//! # no_std
+//!
+//! Without the default `std` feature, the crate is `no_std` and only needs
+//! `alloc`, e.g. for bare-metal RTOS targets. [`Rendezvous`] and its other
+//! kinds of references are kept, but not the timed waits nor the primitives
+//! built on threads. The blocking waits go through the
+//! [`Futex`](rendezvous_core::Futex) set with `set_backend`, and spin until
+//! one is set. Panics cannot be detected then, so that the
+//! [`PanicMode`] of a rendezvous has no effect.
+//!
+//! The `async` feature does not need `std`, for executors such as embassy.
+//! Each rendezvous then has room for the wakers of 4 futures or progress
+//! streams, without locks, and the others wake themselves up on every poll
+//! until a slot frees up.
+//!
+//! In kernels, bootloaders and other environments without any way to park,
+//! the `spin` feature makes the blocking waits spin instead, with
+//! [`spin_loop`](core::hint::spin_loop) hints, and removes `set_backend`.
+//! It has no effect with the `std` feature.
+//!
+//! On targets without native atomic read-modify-write operations, such as
+//! `thumbv6m-none-eabi`, the `portable-atomic` feature emulates them, see
+//! [`rendezvous_core`]. Broadcasting values is not available there.
+//!
+//! # Debugging
+//!
+//! Visualizers rendering a [`Rendezvous`] as its [name](Rendezvous::named),
+//! its number of live and waiting handles, and whether it is poisoned, are
+//! embedded in the crate for WinDbg and Visual Studio (Natvis) and for GDB.
+//! LLDB cannot load embedded scripts, load `debugger/rendezvous_lldb.py` by
+//! hand with `command script import`.
+//!
+//! # Testing
+//!
+//! Building with `--cfg shuttle` (e.g. through `RUSTFLAGS`) makes the waits
+//! yield to the [shuttle](https://docs.rs/shuttle) randomized scheduler
+//! instead of blocking the thread, so that code built on rendezvous can be
+//! tested under shuttle. Its threads must be spawned with
+//! `shuttle::thread::spawn` rather than the spawning helpers of this crate.
+//! The protocol itself can be model-checked with loom, see
+//! [`rendezvous_core`].
+//!
 //! # Other implementations
 //!
 //! There are many other implementations of the same construct, however, this is
@@ -59,79 +129,1261 @@
 //!   offers poisoning and leader election on top of the base functionnalities.
 //!
 //! [`Barrier`]: std::sync::Barrier
-use std::{
+extern crate alloc;
+
+#[cfg(all(not(feature = "std"), target_arch = "wasm32", not(target_feature = "atomics")))]
+compile_error!("The `std` feature is required on WebAssembly without the `atomics` target feature.");
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    cell::UnsafeCell,
     fmt::Debug,
-    mem::forget,
-    ops::Deref,
+    mem::{forget, ManuallyDrop},
     ptr::NonNull,
-    sync::atomic::{AtomicU32, Ordering},
 };
 
-use cache_padded::CachePadded;
+use error::{HandleLimitExceeded, Poisoned, WaitOutcome, WaitTimeout};
+use rendezvous_core::Counters;
+
+mod arrived;
+#[cfg(all(not(feature = "std"), not(feature = "spin")))]
+mod backend;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod barrier;
+mod broadcast;
+mod builder;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "crossbeam")]
+mod crossbeam;
+#[cfg(feature = "deadlock-detection")]
+mod deadlock;
+#[cfg(feature = "registry")]
+pub mod debug;
+#[cfg(all(unix, feature = "ops"))]
+pub mod diagnostics;
+pub mod error;
+#[cfg(all(feature = "std", windows))]
+mod event;
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+mod eventfd;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod exchanger;
+#[cfg(feature = "std")]
+mod fold;
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+mod futex;
+#[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub mod future;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod group;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod gate;
+#[cfg(feature = "debug-handles")]
+mod handles;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub mod handoff;
+#[cfg(all(feature = "rayon", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod help;
+mod hooks;
+#[cfg(all(unix, feature = "ipc"))]
+pub mod ipc;
+mod isr;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod latch;
+mod link;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod multi;
+#[cfg(feature = "net")]
+pub mod net;
+mod observer;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod parallel;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod phaser;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod reusable;
+mod roles;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod scope;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod semaphore;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod set;
+mod slots;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod spawn;
+mod status;
+#[cfg(feature = "metrics")]
+mod telemetry;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+mod wait_group;
+#[cfg(all(feature = "watchdog", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub mod watchdog;
+mod weak;
+
+pub use arrived::Arrived;
+#[cfg(all(not(feature = "std"), not(feature = "spin")))]
+pub use backend::set_backend;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use barrier::Barrier;
+pub use builder::Builder;
+#[cfg(feature = "std")]
+pub use clock::Clock;
+#[cfg(all(feature = "mio", any(target_os = "linux", target_os = "android")))]
+pub use eventfd::CompletionSource;
+#[cfg(feature = "crossbeam")]
+pub use crossbeam::ScopeExt;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use exchanger::Exchanger;
+#[cfg(feature = "std")]
+pub use fold::Fold;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use gate::Gate;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use group::ThreadGroup;
+pub use isr::IsrToken;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use multi::{wait_all, wait_any};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use latch::CountdownLatch;
+pub use observer::{Completion, Observer};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use parallel::{parallel_for, parallel_for_with};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use phaser::Phaser;
+pub use rendezvous_core::{self, Count, PanicMode};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use reusable::ReusableRendezvous;
+pub use roles::{Participant, Waiter};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use scope::{scope, Scope, ScopedRendezvous};
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use semaphore::Semaphore;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use set::RendezvousSet;
+pub use slots::Slots;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use spawn::BuilderExt;
+#[cfg(feature = "stats")]
+pub use stats::WaitStats;
+pub use status::RendezvousStatus;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+pub use wait_group::WaitGroup;
+pub use weak::WeakRendezvous;
 
 /// An adaptive barrier or waitgroup. See the [crate] documentation for more.
 ///
 /// # Remarks
 ///
 /// - There cannot be more than 2³² - 1 simultaneous copies of a single
-///   rendezvous.
+///   rendezvous, or 2⁶⁴ - 1 with the `u64-counters` feature, see [`Count`].
+///   Exceeding it panics, or aborts the process with the
+///   `abort-on-overflow` feature, see [`Rendezvous::try_clone`] otherwise.
 pub struct Rendezvous {
     ptr: NonNull<RDVInner>,
+    // Where this reference was created, see the handles module.
+    #[cfg(feature = "debug-handles")]
+    origin: &'static std::panic::Location<'static>,
+    // The thread deemed to hold this reference, see the deadlock module.
+    #[cfg(feature = "deadlock-detection")]
+    owner: std::thread::ThreadId,
+}
+
+/// Returned by the waits of a [`Rendezvous`] once all the references are
+/// dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaitResult {
+    leader: bool,
+    panicked: u32,
+    #[cfg(feature = "stats")]
+    stats: WaitStats,
+}
+
+impl WaitResult {
+    /// Returns whether this reference is the leader of the rendezvous.
+    ///
+    /// Exactly one of the references which waited until completion is the
+    /// leader, e.g. to perform some cleanup once. If all references are
+    /// dropped without waiting, there is no leader.
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+
+    /// Returns the number of participants which panicked while holding a
+    /// reference, even though the rendezvous was not poisoned, see
+    /// [`PanicMode::Ignore`].
+    pub fn panicked(&self) -> u32 {
+        self.panicked
+    }
 }
 
+/// A callback run once by the last live reference.
+type OnComplete = Option<Box<dyn FnOnce() + Send>>;
+
+/// A callback run on each arrival, with the new live count.
+type OnProgress = Option<Box<dyn Fn(Count) + Send + Sync>>;
+
 struct RDVInner {
-    live: CachePadded<AtomicU32>,
-    alloc_dep: CachePadded<AtomicU32>,
+    counters: Counters,
+    // Empty if anonymous, also read by the debugger visualizers.
+    name: Box<str>,
+    // Only accessed at construction and by the last live reference.
+    on_complete: UnsafeCell<OnComplete>,
+    on_progress: OnProgress,
+    published: broadcast::Published,
+    // Closed by the last live reference.
+    links: link::Links,
+    #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    wakers: future::Wakers,
+    #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    watchers: future::Watchers,
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
+    #[cfg(feature = "debug-handles")]
+    handles: handles::Handles,
+    #[cfg(feature = "deadlock-detection")]
+    owners: deadlock::Owners,
+}
+
+// Diagnostic features must not grow the inner allocation when disabled, see
+// the hooks module.
+#[cfg(not(any(
+    feature = "async",
+    feature = "stats",
+    feature = "debug-handles",
+    feature = "deadlock-detection",
+    all(target_arch = "wasm32", not(target_feature = "atomics"))
+)))]
+const _: () = assert!(
+    core::mem::size_of::<RDVInner>()
+        == core::mem::size_of::<(
+            Counters,
+            Box<str>,
+            OnComplete,
+            OnProgress,
+            broadcast::Published,
+            link::Links,
+        )>()
+);
+
+impl RDVInner {
+    /// Marks a handle as arrived, waking up the waiters if it was the last
+    /// live one, and returns the new live count.
+    fn arrive(&self) -> Count {
+        self.arrive_n(1)
+    }
+
+    /// Marks `n` handles as arrived at once, see [`RDVInner::arrive`].
+    fn arrive_n(&self, n: Count) -> Count {
+        let l = self.counters.arrive_n(n);
+        self.arrived(n, l);
+        l
+    }
+
+    /// Same as [`Counters::arrive_cancellable`], see [`RDVInner::arrive`].
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    fn arrive_cancellable(&self) -> Count {
+        let l = self.counters.arrive_cancellable();
+        self.arrived(1, l);
+        l
+    }
+
+    /// Same as [`Counters::arrive_if_last`], waking up the waiters if the
+    /// handle was the last live one.
+    fn arrive_if_last(&self) -> bool {
+        let last = self.counters.arrive_if_last();
+        if last {
+            self.arrived(1, 0);
+        }
+        last
+    }
+
+    /// Reacts to the arrival of `n` handles, which left `l` live ones.
+    fn arrived(&self, n: Count, l: Count) {
+        hooks::arrived(self, n, l);
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(l);
+        }
+        #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        self.watchers.wake_all();
+        if l == 0 {
+            // We were the last live barrier
+            //TODO(arthur): maybe do only if there are waiting threads
+            self.wake_all();
+            // Closed even if the callback panics, so that what is linked to
+            // the completion, e.g. merged rendezvous, does not wait forever.
+            let _closing = self.links.closing();
+            // Safety: live reaches zero only once, so we are the only ones
+            // accessing the callback since construction.
+            if let Some(on_complete) = unsafe { &mut *self.on_complete.get() }.take() {
+                on_complete();
+            }
+        } else if l == 1 {
+            self.wake_awaiting_others();
+        }
+    }
+
+    /// Same as [`Counters::arrive_if_not_last`], waking up the last live
+    /// handle if it is waiting for the others.
+    fn arrive_if_not_last(&self) -> Option<Count> {
+        let l = self.counters.arrive_if_not_last();
+        if let Some(l) = l {
+            hooks::arrived(self, 1, l);
+        }
+        if let (Some(on_progress), Some(l)) = (&self.on_progress, l) {
+            on_progress(l);
+        }
+        #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        if l.is_some() {
+            self.watchers.wake_all();
+        }
+        if l == Some(1) {
+            self.wake_awaiting_others();
+        }
+        l
+    }
+
+    /// Wakes up the last live handle if it may be waiting for the others in
+    /// [`Rendezvous::wait_ref`].
+    fn wake_awaiting_others(&self) {
+        #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        if self.counters.awaits_others() {
+            self.counters.wake_others::<futex::Futex>();
+        }
+    }
+
+    /// Registers `n` new references, see [`handle_limit_exceeded`].
+    fn register_n(&self, n: Count) {
+        if !self.counters.try_register_n(n) {
+            handle_limit_exceeded();
+        }
+    }
+
+    /// Registers a new dependency on the allocation, see
+    /// [`handle_limit_exceeded`].
+    fn retain(&self) {
+        if !self.counters.try_retain() {
+            handle_limit_exceeded();
+        }
+    }
+
+    /// Returns the result of a wait once the rendezvous is complete, electing
+    /// the leader if it is not poisoned.
+    fn result(&self) -> Result<WaitResult, Poisoned> {
+        self.check_poisoned()?;
+        Ok(WaitResult {
+            leader: self.counters.elect(),
+            panicked: self.counters.panicked(),
+            #[cfg(feature = "stats")]
+            stats: WaitStats::default(),
+        })
+    }
+
+    fn check_poisoned(&self) -> Result<(), Poisoned> {
+        if self.counters.is_poisoned() {
+            Err(Poisoned {
+                panicked: self.counters.panicked(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn wake_all(&self) {
+        // Blocking and async waiters can wait on the same rendezvous, so both
+        // kinds are woken up.
+        #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        self.counters.wake_all::<futex::Futex>();
+        #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        self.wakers.wake_all();
+    }
+}
+
+/// Gives up one allocation dependency on the pointed inner, freeing it if it
+/// was the last one.
+///
+/// # Safety
+///
+/// `ptr` must be valid and the caller must own one of the allocation
+/// dependencies, which it must not use anymore afterwards.
+unsafe fn release(ptr: NonNull<RDVInner>) {
+    // Safety: forwarded to the caller.
+    unsafe { release_n(ptr, 1) };
+}
+
+/// Gives up `n` allocation dependencies on the pointed inner at once, see
+/// [`release`].
+///
+/// # Safety
+///
+/// Same as [`release`], for `n` allocation dependencies.
+unsafe fn release_n(ptr: NonNull<RDVInner>, n: Count) {
+    // Safety: the caller owns allocation dependencies, so the pointer is
+    // valid until this very instruction which gives them up.
+    if unsafe { ptr.as_ref() }.counters.release_n(n) {
+        hooks::freeing(ptr);
+        // Safety: we were the last alloc_dependent barrier so nobody else
+        // is trying to drop the inner and we can do it.
+        drop(unsafe { Box::from_raw(ptr.as_ptr()) });
+    }
+}
+
+/// Elects the leader among the waiters of a completed rendezvous, unless it
+/// is poisoned, then gives up one allocation dependency on the pointed
+/// inner.
+///
+/// # Safety
+///
+/// Same as [`release`].
+unsafe fn finish(ptr: NonNull<RDVInner>) -> Result<WaitResult, Poisoned> {
+    // Safety: the caller owns an allocation dependency, so the pointer is
+    // valid.
+    let inner = unsafe { ptr.as_ref() };
+    let (result, mode) = (inner.result(), inner.counters.panic_mode());
+    // Safety: forwarded to the caller.
+    unsafe { release(ptr) };
+    report(result, mode)
+}
+
+/// Returns whether the current thread is panicking, which is never known
+/// without the `std` feature.
+fn panicking() -> bool {
+    #[cfg(feature = "std")]
+    return std::thread::panicking();
+    #[cfg(not(feature = "std"))]
+    false
+}
+
+/// Called when there would be more than 2³² - 1 handles on a rendezvous, or
+/// 2⁶⁴ - 1 with the `u64-counters` feature.
+///
+/// With the `abort-on-overflow` feature, this aborts the process instead of
+/// panicking, for programs which catch panics: the counters are left
+/// unchanged, but the caller may not expect to lose its new reference.
+#[cold]
+#[track_caller]
+fn handle_limit_exceeded() -> ! {
+    #[cfg(not(feature = "u64-counters"))]
+    const MESSAGE: &str = "There should not be more than 2^32 - 1 clones of one Rendezvous.";
+    #[cfg(feature = "u64-counters")]
+    const MESSAGE: &str = "There should not be more than 2^64 - 1 clones of one Rendezvous.";
+    #[cfg(feature = "abort-on-overflow")]
+    {
+        eprintln!("{MESSAGE}");
+        std::process::abort();
+    }
+    #[cfg(not(feature = "abort-on-overflow"))]
+    panic!("{MESSAGE}");
+}
+
+/// Reports the result of a wait according to the panic mode of the
+/// rendezvous.
+///
+/// # Panics
+///
+/// If the rendezvous is poisoned with [`PanicMode::Propagate`].
+fn report<T>(result: Result<T, Poisoned>, mode: PanicMode) -> Result<T, Poisoned> {
+    if let (Err(poisoned), PanicMode::Propagate) = (&result, mode) {
+        panic!("{poisoned}");
+    }
+    result
 }
 
 impl Rendezvous {
     /// Creates a new `Rendezvous`. Clone it so that other threads can
     /// synchronize on it.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
     pub fn new() -> Self {
+        Self::from_parts(Counters::new(), Box::default(), None, None)
+    }
+
+    /// Creates a new `Rendezvous` handling the panics of its participants
+    /// according to `mode`.
+    ///
+    /// A reference dropped while its thread is panicking, typically because
+    /// the participant holding it panicked, poisons the rendezvous unless
+    /// `mode` is [`PanicMode::Ignore`]. Once all references are dropped, the
+    /// waits on a poisoned rendezvous then return [`Poisoned`] with
+    /// [`PanicMode::Poison`], and panic with [`PanicMode::Propagate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::{PanicMode, Rendezvous};
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::with_panic_mode(PanicMode::Poison);
+    /// let worker = rdv.clone();
+    /// thread::spawn(move || {
+    ///     let _worker = worker;
+    ///     panic!("the worker failed");
+    /// });
+    /// assert_eq!(rdv.wait().unwrap_err().panicked(), 1);
+    /// ```
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn with_panic_mode(mode: PanicMode) -> Self {
+        Self::from_parts(Counters::with_panic_mode(mode), Box::default(), None, None)
+    }
+
+    /// Creates a new rendezvous with `n` references, e.g. one per worker
+    /// known in advance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let mut rdvs = Rendezvous::with_count(5);
+    /// let rdv = rdvs.pop().unwrap();
+    /// for worker in rdvs {
+    ///     thread::spawn(move || drop(worker));
+    /// }
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn with_count(n: Count) -> Vec<Self> {
+        let rdv = Self::from_parts(Counters::new().with_count(n), Box::default(), None, None);
+        // It is one of the n references.
+        let ptr = rdv.into_ptr();
+        (0..n).map(|_| Self::from_ptr(ptr)).collect()
+    }
+
+    /// Creates a new `Rendezvous` named `name`, as shown by its
+    /// [`Debug`] implementation and the debugger visualizers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::named("stage-3");
+    /// assert_eq!(rdv.name(), Some("stage-3"));
+    /// assert!(format!("{rdv:?}").contains("stage-3"));
+    /// ```
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::from_parts(Counters::new(), name.into().into_boxed_str(), None, None)
+    }
+
+    /// Returns the name of the rendezvous, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        Some(&*inner.name).filter(|name| !name.is_empty())
+    }
+
+    /// Returns a [`Builder`] to create a `Rendezvous` with several options.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Returns `n` new references to the rendezvous, registered at once.
+    ///
+    /// This is cheaper than cloning this reference `n` times, e.g. to spawn
+    /// many workers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// for worker in rdv.clone_n(4) {
+    ///     thread::spawn(move || {
+    ///         // Do some work.
+    ///         drop(worker);
+    ///     });
+    /// }
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If there would be more than 2³² - 1 copies of the rendezvous.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn clone_n(&self, n: Count) -> Vec<Rendezvous> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.register_n(n);
+        hooks::registered(inner, n);
+        (0..n).map(|_| Self::from_ptr(self.ptr)).collect()
+    }
+
+    /// Returns a new reference to the rendezvous, like [`Clone::clone`], but
+    /// without panicking if the rendezvous already has as many references as
+    /// it can.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.try_clone().expect("far from the limit");
+    /// drop(worker);
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If there would be more than 2³² - 1 copies of the rendezvous, which
+    /// is left unchanged.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn try_clone(&self) -> Result<Rendezvous, HandleLimitExceeded> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        if !inner.counters.try_register_n(1) {
+            return Err(HandleLimitExceeded);
+        }
+        hooks::registered(inner, 1);
+        Ok(Self::from_ptr(self.ptr))
+    }
+
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn from_parts(
+        counters: Counters,
+        name: Box<str>,
+        on_complete: OnComplete,
+        on_progress: OnProgress,
+    ) -> Self {
         let boxed = Box::new(RDVInner {
-            live: CachePadded::new(AtomicU32::new(1)),
-            alloc_dep: CachePadded::new(AtomicU32::new(1)),
+            counters,
+            name,
+            on_complete: UnsafeCell::new(on_complete),
+            on_progress,
+            published: Default::default(),
+            links: Default::default(),
+            #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+            wakers: Default::default(),
+            #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+            watchers: Default::default(),
+            #[cfg(feature = "stats")]
+            stats: Default::default(),
+            #[cfg(feature = "debug-handles")]
+            handles: Default::default(),
+            #[cfg(feature = "deadlock-detection")]
+            owners: Default::default(),
         });
-        Self {
-            // SAFETY: Box::into_raw cannot be null.
-            ptr: unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) },
-        }
+        // SAFETY: Box::into_raw cannot be null.
+        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(boxed)) };
+        hooks::created(ptr);
+        Self::from_ptr(ptr)
     }
 
-    /// Drops this reference and waits until all other references are dropped.
-    pub fn wait(self) {
+    /// Wraps `ptr`, handing over to the new reference a live registration
+    /// and an allocation dependency owned by the caller.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn from_ptr(ptr: NonNull<RDVInner>) -> Self {
+        let rdv = Self {
+            ptr,
+            #[cfg(feature = "debug-handles")]
+            origin: std::panic::Location::caller(),
+            #[cfg(feature = "deadlock-detection")]
+            owner: std::thread::current().id(),
+        };
+        hooks::handle_created(&rdv);
+        rdv
+    }
+
+    /// Records that this reference was moved to the current thread, e.g. by
+    /// a spawned thread, for the diagnostic features.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    fn adopt(mut self) -> Self {
+        hooks::handle_moved(&mut self);
+        self
+    }
+
+    /// Gives up this reference without arriving, handing its live
+    /// registration and allocation dependency over to the caller.
+    fn into_ptr(self) -> NonNull<RDVInner> {
+        hooks::handle_gone(&self);
         let ptr = self.ptr;
         forget(self);
+        ptr
+    }
+
+    /// Drops this reference and waits until all other references are dropped.
+    ///
+    /// Exactly one of the references waiting on a rendezvous, through this
+    /// method or any other, is told it is the [leader](WaitResult::is_leader).
+    /// If the rendezvous is poisoned, [`Poisoned`] is returned instead, see
+    /// [`with_panic_mode`](Rendezvous::with_panic_mode).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let handles: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let rdv = rdv.clone();
+    ///         thread::spawn(move || rdv.wait())
+    ///     })
+    ///     .collect();
+    /// let mut leaders = usize::from(rdv.wait().unwrap().is_leader());
+    /// for handle in handles {
+    ///     leaders += usize::from(handle.join().unwrap().unwrap().is_leader());
+    /// }
+    /// assert_eq!(leaders, 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`].
+    ///
+    /// # Remarks
+    ///
+    /// - On WebAssembly without the `atomics` target feature, as found in
+    ///   browsers without `SharedArrayBuffer`, the only thread cannot block:
+    ///   this method panics if other references are live, use
+    ///   [`wait_async`](Rendezvous::wait_async) instead.
+    /// - With the `deadlock-detection` feature, a wait lasting while the
+    ///   calling thread holds all the remaining references reports a likely
+    ///   deadlock, e.g. when one of them is stored in a struct it owns.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait(self) -> Result<WaitResult, Poisoned> {
+        let ptr = self.into_ptr();
         // Scope-invariant:
         // inner.alloc_dep > 0
         // which implies that self.ptr is still valid
-        {
+        let waited = {
             // Safety: Because of the scope invariant
             // the pointer will remain valid until the scope's end.
             let inner = unsafe { ptr.as_ref() };
-            let mut l = inner.live.fetch_sub(1, Ordering::AcqRel) - 1;
-            if l == 0 {
-                // We were the last live barrier
-                atomic_wait::wake_all(inner.live.deref());
-            }
-            while l > 0 {
-                // There are still some live barriers
-                atomic_wait::wait(&inner.live, l);
-                l = inner.live.load(Ordering::Acquire);
-            }
+            let waiting = hooks::waiting(inner);
+            let l = inner.arrive();
+            hooks::wait(inner, l);
+            waiting.waited(l)
+        };
+        // Safety: the invariant from the scope above is still true and we
+        // forgot self so we own its allocation dependency.
+        waited.complete(unsafe { finish(ptr) })
+    }
+
+    /// Drops this reference, which must be the only live one, and returns
+    /// right away.
+    ///
+    /// # Panics
+    ///
+    /// If other references are live, which the only thread of WebAssembly
+    /// without the `atomics` target feature would wait for forever: use
+    /// [`wait_async`](Rendezvous::wait_async) instead.
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`].
+    #[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+    #[track_caller]
+    pub fn wait(self) -> Result<WaitResult, Poisoned> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        // Checked before arriving, so that self is still dropped normally.
+        assert!(
+            inner.counters.live() == 1,
+            "Waiting on a rendezvous with other live references would block the only thread forever, use wait_async instead."
+        );
+        let ptr = self.into_ptr();
+        // Safety: we forgot self so we own its allocation dependency, which
+        // keeps the ptr valid.
+        let l = unsafe { ptr.as_ref() }.arrive();
+        debug_assert_eq!(l, 0);
+        // Safety: we forgot self so we own its allocation dependency.
+        unsafe { finish(ptr) }
+    }
+
+    /// Drops this reference and waits until all other references are dropped,
+    /// running `f` first if this reference is the last one.
+    ///
+    /// When this reference arrives last, `f` runs before any waiter is
+    /// released, as a critical section between two phases, and its result
+    /// is returned. Otherwise `f` is dropped and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::{
+    ///     sync::{Arc, Mutex},
+    ///     thread,
+    /// };
+    ///
+    /// // The front and back buffers.
+    /// let buffers = Arc::new(Mutex::new((0, 0)));
+    /// let swap = |buffers: &Mutex<(u32, u32)>| {
+    ///     let (front, back) = &mut *buffers.lock().unwrap();
+    ///     std::mem::swap(front, back);
+    /// };
+    /// let rdv = Rendezvous::new();
+    /// for i in 1..=4 {
+    ///     let (rdv, buffers) = (rdv.clone(), buffers.clone());
+    ///     thread::spawn(move || {
+    ///         buffers.lock().unwrap().1 += i;
+    ///         rdv.wait_and_then(|| swap(&buffers))
+    ///     });
+    /// }
+    /// rdv.wait_and_then(|| swap(&buffers)).unwrap();
+    /// assert_eq!(buffers.lock().unwrap().0, 10);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`].
+    ///
+    /// # Remarks
+    ///
+    /// - `f` does not run if the last reference to arrive is dropped rather
+    ///   than waiting, or if the rendezvous is poisoned.
+    /// - If `f` panics, this reference is dropped while panicking, see
+    ///   [`with_panic_mode`](Rendezvous::with_panic_mode).
+    /// - This does not take part in the [leader](WaitResult::is_leader)
+    ///   election.
+    /// - While `f` runs, [weak references](WeakRendezvous) cannot be
+    ///   upgraded, nor can the timed out waits be cancelled, since the
+    ///   rendezvous is as good as complete.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait_and_then<F, R>(self, f: F) -> Result<Option<R>, Poisoned>
+    where
+        F: FnOnce() -> R,
+    {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        let mode = inner.counters.panic_mode();
+        let Some(l) = inner.arrive_if_not_last() else {
+            // We are the last live reference, nobody can join, be released
+            // nor poison the rendezvous before we finish.
+            let finishing = Finishing(ManuallyDrop::new(self));
+            let result = inner.check_poisoned().map(|()| f());
+            drop(finishing);
+            return report(result.map(Some), mode);
+        };
+        let ptr = self.into_ptr();
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.ptr is still valid
+        let result = {
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { ptr.as_ref() };
+            hooks::wait(inner, l);
+            inner.check_poisoned()
+        };
+        // Safety: the invariant from the scope above is still true and we
+        // forgot self so we own its allocation dependency.
+        unsafe { release(ptr) };
+        report(result.map(|()| None), mode)
+    }
+
+    /// Waits until all other references are dropped, keeping this one.
+    ///
+    /// This reference stays live, so that it can be cloned again afterwards,
+    /// e.g. for a second wave of workers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// for wave in 0..2 {
+    ///     for _ in 0..4 {
+    ///         let rdv = rdv.clone();
+    ///         thread::spawn(move || {
+    ///             // Do the work of this wave.
+    ///             drop(rdv);
+    ///         });
+    ///     }
+    ///     rdv.wait_ref().unwrap();
+    /// }
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`].
+    ///
+    /// # Remarks
+    ///
+    /// - The other references waiting on the rendezvous keep waiting for this
+    ///   one, they are not released.
+    /// - References waiting with a timeout, e.g. with
+    ///   [`wait_timeout`](Rendezvous::wait_timeout), may give up and be live
+    ///   again, so they only count once their wait returns.
+    /// - Two references waiting this way at the same time wait for each other
+    ///   forever.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait_ref(&self) -> Result<(), Poisoned> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.counters.wait_others::<futex::Futex>();
+        report(inner.check_poisoned(), inner.counters.panic_mode())
+    }
+
+    /// Re-arms the rendezvous for a new round, reusing its allocation, if
+    /// this is its only reference. Returns whether it was re-armed.
+    ///
+    /// The rendezvous is then neither poisoned nor with a leader, and
+    /// without a published value. Since this reference stayed live, the
+    /// rendezvous did not complete though: its
+    /// [completion callback](Builder::on_complete) did not run, and the
+    /// rendezvous [merged](Rendezvous::merge) with it still wait for it. Both
+    /// are kept for the round which completes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let mut rdv = Rendezvous::new();
+    /// for _round in 0..3 {
+    ///     let workers: Vec<_> = (0..4)
+    ///         .map(|_| {
+    ///             let rdv = rdv.clone();
+    ///             thread::spawn(move || drop(rdv))
+    ///         })
+    ///         .collect();
+    ///     rdv.wait_ref().unwrap();
+    ///     # for worker in workers { worker.join().unwrap(); }
+    ///     assert!(rdv.reset());
+    /// }
+    /// ```
+    ///
+    /// Nesting rendezvous in a re-armed one, or completing it:
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::{sync::mpsc, thread};
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let mut rdv = Rendezvous::builder()
+    ///     .on_complete(move || tx.send("done").unwrap())
+    ///     .build();
+    /// rdv.wait_ref().unwrap();
+    /// assert!(rdv.reset());
+    ///
+    /// let child = rdv.child();
+    /// let worker = child.clone();
+    /// thread::spawn(move || drop(worker));
+    /// child.wait().unwrap();
+    /// // The child completed, and released the rendezvous.
+    /// rdv.wait().unwrap();
+    /// assert_eq!(rx.recv(), Ok("done"));
+    /// ```
+    pub fn reset(&mut self) -> bool {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        if inner.counters.alloc_dep() != 1 {
+            return false;
         }
-        // Safety: the invariant from the scope above is still true
-        // and is broken in this very instruction
-        if unsafe { ptr.as_ref() }
-            .alloc_dep
-            .fetch_sub(1, Ordering::AcqRel)
-            == 1
-        {
-            // Safety: we were the last alloc_dependent barrier so nobody else
-            // is trying to drop the inner and we can do it.
-            unsafe { Box::from_raw(ptr.as_ptr()) };
+        hooks::resetting(|| {
+            inner.counters.reset();
+            // Safety: self is the only reference, and is borrowed mutably, so
+            // nobody else accesses the published value.
+            unsafe { *inner.published.get() = None };
+        });
+        true
+    }
+
+    /// Drops this reference and waits until all other references are dropped,
+    /// or until `timeout` has elapsed, in which case the reference is given
+    /// back.
+    ///
+    /// The timeout is measured on [`Clock::Monotonic`], see
+    /// [`wait_timeout_with`](Rendezvous::wait_timeout_with) to pick another
+    /// clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::{error::WaitOutcome, Rendezvous};
+    /// use std::time::Duration;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    ///
+    /// // The worker is still holding its reference.
+    /// let Err(WaitOutcome::TimedOut(timeout)) = rdv.wait_timeout(Duration::from_millis(10)) else {
+    ///     unreachable!();
+    /// };
+    /// let rdv = timeout.into_inner();
+    ///
+    /// drop(worker);
+    /// rdv.wait_timeout(Duration::from_millis(10)).unwrap();
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - The reference given back on timeout, in [`WaitTimeout`], is live
+    ///   again, exactly as before the call: it can be waited on again, or
+    ///   dropped.
+    /// - The wait may last longer than `timeout`, depending on when the
+    ///   waiting thread gets scheduled again.
+    /// - This method is not available on WebAssembly without the `atomics`
+    ///   target feature, where the only thread cannot block: use
+    ///   [`wait_async`](Rendezvous::wait_async) instead.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn wait_timeout(self, timeout: std::time::Duration) -> Result<WaitResult, WaitOutcome> {
+        self.wait_timeout_with(timeout, Clock::Monotonic)
+    }
+
+    /// Same as [`wait_timeout`](Rendezvous::wait_timeout), with the timeout
+    /// measured on `clock`.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn wait_timeout_with(
+        self,
+        timeout: std::time::Duration,
+        clock: Clock,
+    ) -> Result<WaitResult, WaitOutcome> {
+        let Some(deadline) = clock.now().checked_add(timeout) else {
+            // A deadline which cannot be represented cannot be reached.
+            return Ok(self.wait()?);
+        };
+        self.wait_until(deadline, || clock.now())
+    }
+
+    /// Drops this reference and waits until all other references are dropped,
+    /// or until `deadline` is reached, in which case the reference is given
+    /// back.
+    ///
+    /// This is the same as [`wait_timeout`](Rendezvous::wait_timeout), with
+    /// an absolute deadline rather than a duration, so that waiting several
+    /// times against the same deadline does not need to keep track of the
+    /// remaining time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// let first = Rendezvous::new();
+    /// let second = Rendezvous::new();
+    /// let worker = second.clone();
+    ///
+    /// first.wait_deadline(deadline).unwrap();
+    /// // The worker is still holding its reference.
+    /// assert!(second.wait_deadline(deadline).is_err());
+    /// # drop(worker);
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - A deadline in the past does not block, but still succeeds if no
+    ///   other reference is live.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn wait_deadline(self, deadline: std::time::Instant) -> Result<WaitResult, WaitOutcome> {
+        let start = std::time::Instant::now();
+        self.wait_until(deadline.saturating_duration_since(start), || {
+            start.elapsed()
+        })
+    }
+
+    /// Drops this reference if all other references are dropped already,
+    /// and gives it back otherwise, without ever blocking.
+    ///
+    /// This is the same as [`wait_timeout`](Rendezvous::wait_timeout) with a
+    /// zero timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    ///
+    /// let rdv = rdv.try_wait().unwrap_err().into_rendezvous().unwrap();
+    /// drop(worker);
+    /// rdv.try_wait().unwrap();
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - If this reference turns out to be the last live one, it wakes up
+    ///   the other waiters as dropping it would.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn try_wait(self) -> Result<WaitResult, WaitOutcome> {
+        let ptr = self.into_ptr();
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.ptr is still valid
+        let complete = {
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { ptr.as_ref() };
+            inner.arrive_if_last()
+        };
+        if complete {
+            // Safety: the invariant from the scope above is still true and we
+            // forgot self so we own its allocation dependency.
+            Ok(unsafe { finish(ptr) }?)
+        } else {
+            // The arrival was cancelled, the allocation dependency and the
+            // live registration are handed back.
+            Err(WaitTimeout::new(Rendezvous::from_ptr(ptr)).into())
         }
     }
+
+    /// Common part of the timed waits, `now` reading the clock against which
+    /// `deadline` is measured.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_feature = "atomics")))))]
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn wait_until(
+        self,
+        deadline: std::time::Duration,
+        now: impl FnMut() -> std::time::Duration,
+    ) -> Result<WaitResult, WaitOutcome> {
+        let ptr = self.into_ptr();
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.ptr is still valid
+        let (complete, waited) = {
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { ptr.as_ref() };
+            let waiting = hooks::waiting(inner);
+            let l = inner.arrive_cancellable();
+            let complete = inner
+                .counters
+                .wait_deadline::<futex::Futex>(l, deadline, now);
+            (complete, waiting.waited(l))
+        };
+        if complete {
+            // Safety: the invariant from the scope above is still true and we
+            // forgot self so we own its allocation dependency.
+            Ok(waited.complete(unsafe { finish(ptr) })?)
+        } else {
+            // The arrival was cancelled, the allocation dependency and the
+            // live registration are handed back.
+            Err(WaitTimeout::new(Rendezvous::from_ptr(ptr)).into())
+        }
+    }
+
+    /// Drops this reference and returns a future resolving once all other
+    /// references are dropped.
+    ///
+    /// The future is woken by the last reference to be dropped and does not
+    /// depend on a specific executor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn example() {
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// std::thread::spawn(move || {
+    ///     // Do some work.
+    ///     drop(worker);
+    /// });
+    /// // Blocking and async waiters can be mixed.
+    /// let blocking = rdv.clone();
+    /// std::thread::spawn(move || blocking.wait());
+    /// rdv.wait_async().await;
+    /// # }
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `async` feature, except on WebAssembly
+    ///   without the `atomics` target feature, as found in browsers without
+    ///   `SharedArrayBuffer`, where it is the only way to wait.
+    #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait_async(self) -> future::WaitFuture {
+        let ptr = self.into_ptr();
+        // Safety: we forgot self so we own its allocation dependency, which
+        // keeps the pointer valid.
+        unsafe { ptr.as_ref() }.arrive();
+        // Safety: the allocation dependency is transferred to the future.
+        unsafe { future::WaitFuture::new(ptr) }
+    }
+
+    /// Returns the number of live references, including this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// assert_eq!(rdv.remaining(), 2);
+    /// drop(worker);
+    /// assert_eq!(rdv.remaining(), 1);
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - The count may change as soon as it is read, as other references
+    ///   are cloned or dropped concurrently. It can only be relied upon as
+    ///   a hint, e.g. for reporting.
+    pub fn remaining(&self) -> Count {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.counters.live()
+    }
+
+    /// Returns whether this is the only live reference, in which case
+    /// [`wait`](Rendezvous::wait) would return right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// assert!(!rdv.is_unique());
+    /// drop(worker);
+    /// assert!(rdv.is_unique());
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - Once `true`, it stays so until this reference is cloned or a
+    ///   [`WeakRendezvous`] is upgraded, as there is no other live reference
+    ///   to clone.
+    pub fn is_unique(&self) -> bool {
+        self.remaining() == 1
+    }
+
+    /// Returns whether both references are to the same rendezvous.
+    ///
+    /// This is also what `==` compares, and what [`Hash`](std::hash::Hash)
+    /// hashes, so that references can be used as keys in maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// assert!(rdv.ptr_eq(&rdv.clone()));
+    /// assert!(!rdv.ptr_eq(&Rendezvous::new()));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
 }
 
 impl Drop for Rendezvous {
@@ -143,37 +1395,52 @@ impl Drop for Rendezvous {
             // Safety: Because of the scope invariant
             // the pointer will remain valid until the scope's end.
             let inner = unsafe { self.ptr.as_ref() };
-            if inner.live.fetch_sub(1, Ordering::AcqRel) == 1 {
-                //TODO(arthur): maybe do only if there are waiting threads
-                atomic_wait::wake_all(inner.live.deref());
+            hooks::handle_gone(self);
+            if panicking() {
+                inner.counters.record_panic();
             }
+            inner.arrive();
         }
-        // Safety: the invariant from the scope above is still true
-        // and is broken in this very instruction
-        if unsafe { self.ptr.as_ref() }
-            .alloc_dep
-            .fetch_sub(1, Ordering::AcqRel)
-            == 1
+        // Safety: the invariant from the scope above is still true and self
+        // is being dropped so we own its allocation dependency.
+        unsafe { release(self.ptr) };
+    }
+}
+
+/// The last live reference once [`RDVInner::arrive_if_not_last`] returned
+/// `None`, which arrives with [`Counters::finish`] when dropped.
+pub(crate) struct Finishing(pub(crate) ManuallyDrop<Rendezvous>);
+
+impl Drop for Finishing {
+    fn drop(&mut self) {
+        // Scope-invariant:
+        // inner.alloc_dep > 0
+        // which implies that self.0.ptr is still valid
         {
-            // Safety: we were the last alloc_dependent barrier so nobody else
-            // is trying to drop the inner and we can do it.
-            unsafe { Box::from_raw(self.ptr.as_ptr()) };
+            // Safety: Because of the scope invariant
+            // the pointer will remain valid until the scope's end.
+            let inner = unsafe { self.0.ptr.as_ref() };
+            hooks::handle_gone(&self.0);
+            if panicking() {
+                inner.counters.record_panic();
+            }
+            inner.counters.finish();
+            inner.arrived(1, 0);
         }
+        // Safety: the invariant from the scope above is still true and the
+        // reference is never dropped so we own its allocation dependency.
+        unsafe { release(self.0.ptr) };
     }
 }
 
 impl Clone for Rendezvous {
+    #[cfg_attr(feature = "debug-handles", track_caller)]
     fn clone(&self) -> Self {
         // Safety: self exist so the ptr is valid
         let inner = unsafe { self.ptr.as_ref() };
-        inner
-            .alloc_dep
-            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |n| n.checked_add(1))
-            .expect("There should not be more than 2^32 - 1 clones of one Rendezvous.");
-        // This one cannot overflow because live < alloc_dep
-        // at all times
-        inner.live.fetch_add(1, Ordering::AcqRel);
-        Self { ptr: self.ptr }
+        inner.register_n(1);
+        hooks::registered(inner, 1);
+        Self::from_ptr(self.ptr)
     }
 }
 
@@ -181,29 +1448,56 @@ impl Clone for Rendezvous {
 
 // Safety: it is send by design.
 unsafe impl Send for Rendezvous {}
-// Safety: this is also sync:
-// all methods taking self by reference (only clone for now) only use it as a
-// smart pointer and do not change the allocation.
+// Safety: this is also sync. The methods taking self by shared reference
+// either use it as a smart pointer to register other handles (e.g. clone,
+// try_clone, child, merge, downgrade), or only read and wait on the atomic
+// counters (e.g. wait_ref, remaining, panicked), or read the name, which
+// never changes. The non-atomic parts of the allocation, the published value
+// and the completion callback, are only written through &mut self (reset),
+// or by the last live reference once the others are gone (arrived,
+// finish_with), and the published value is only read once the rendezvous
+// completed.
 unsafe impl Sync for Rendezvous {}
 
 // Common traits implementations
 
+impl PartialEq for Rendezvous {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl Eq for Rendezvous {}
+
+impl core::hash::Hash for Rendezvous {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
 impl Default for Rendezvous {
+    #[cfg_attr(feature = "debug-handles", track_caller)]
     fn default() -> Self {
         Self::new()
     }
 }
 
 impl Debug for Rendezvous {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // Safety: self exist so the ptr is valid
         let inner = unsafe { self.ptr.as_ref() };
-        f.debug_struct("Rendezvous")
-            .field("live barriers", &inner.live.load(Ordering::Acquire))
+        let mut f = f.debug_struct("Rendezvous");
+        if let Some(name) = self.name() {
+            f.field("name", &name);
+        }
+        f.field("live barriers", &inner.counters.live())
             .field(
                 "total allocations (live + waiting)",
-                &inner.alloc_dep.load(Ordering::Acquire),
+                &inner.counters.alloc_dep(),
             )
+            .field("blocked waiters", &inner.counters.parked())
+            .field("poisoned", &inner.counters.is_poisoned())
+            .field("panicked", &inner.counters.panicked())
             .finish()
     }
 }
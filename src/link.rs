@@ -0,0 +1,175 @@
+//! Rendezvous whose completion is linked to other ones, e.g. nested ones.
+
+use alloc::boxed::Box;
+use core::{
+    ptr::{self, NonNull},
+    sync::atomic::Ordering,
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::AtomicPtr;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::AtomicPtr;
+
+use crate::{hooks, Rendezvous};
+
+/// Callbacks run once the rendezvous holding them completes, e.g. dropping
+/// references to other rendezvous.
+///
+/// This is a lock-free stack, closed by the last live reference.
+#[derive(Default)]
+pub(crate) struct Links {
+    head: AtomicPtr<Link>,
+}
+
+struct Link {
+    callback: Box<dyn FnOnce() + Send>,
+    next: *mut Link,
+}
+
+/// The head of closed links, which never is the address of a link.
+fn closed() -> *mut Link {
+    NonNull::dangling().as_ptr()
+}
+
+impl Links {
+    /// Runs `callback` once the rendezvous holding the links completes, or
+    /// immediately if it did already.
+    pub(crate) fn push<F: FnOnce() + Send + 'static>(&self, callback: F) {
+        let link = Box::into_raw(Box::new(Link {
+            callback: Box::new(callback),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head == closed() {
+                // Safety: the link was not shared.
+                (unsafe { Box::from_raw(link) }.callback)();
+                return;
+            }
+            // Safety: the link is not shared until the exchange succeeds.
+            unsafe { (*link).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, link, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Runs all the callbacks, as well as those pushed afterwards.
+    pub(crate) fn close(&self) {
+        let mut link = self.head.swap(closed(), Ordering::AcqRel);
+        while !link.is_null() && link != closed() {
+            // Safety: the links were pushed by push, and we took them all.
+            let Link { callback, next } = *unsafe { Box::from_raw(link) };
+            callback();
+            link = next;
+        }
+    }
+
+    /// Returns a guard closing the links once dropped, even while panicking.
+    pub(crate) fn closing(&self) -> Closing<'_> {
+        Closing(self)
+    }
+}
+
+/// Closes the links once dropped, see [`Links::closing`].
+pub(crate) struct Closing<'a>(&'a Links);
+
+impl Drop for Closing<'_> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// A reference held by links, e.g. to the parent of a
+/// [child](Rendezvous::child), rather than by the thread which created it.
+pub(crate) struct Linked(Rendezvous);
+
+impl Linked {
+    pub(crate) fn new(rdv: Rendezvous) -> Self {
+        hooks::handle_linked(&rdv);
+        Self(rdv)
+    }
+}
+
+impl Drop for Linked {
+    fn drop(&mut self) {
+        hooks::handle_unlinked(&self.0);
+    }
+}
+
+impl Drop for Links {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl Rendezvous {
+    /// Returns a new rendezvous which completes once both this one and
+    /// `other` completed, and the returned reference is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (network, storage) = (Rendezvous::new(), Rendezvous::new());
+    /// let shutdown = network.merge(&storage);
+    /// for subsystem in [network, storage] {
+    ///     thread::spawn(move || drop(subsystem));
+    /// }
+    /// shutdown.wait().unwrap();
+    /// ```
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn merge(&self, other: &Rendezvous) -> Rendezvous {
+        let merged = Rendezvous::new();
+        for rdv in [self, other] {
+            // Safety: rdv exist so the ptr is valid
+            let inner = unsafe { rdv.ptr.as_ref() };
+            let merged = Linked::new(merged.clone());
+            inner.links.push(move || drop(merged));
+        }
+        merged
+    }
+
+    /// Returns a new rendezvous nested in this one, which counts as a single
+    /// live reference of this one until it completes.
+    ///
+    /// The child handles panics the same way as this rendezvous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let pipeline = Rendezvous::new();
+    /// for _stage in 0..2 {
+    ///     let stage = pipeline.child();
+    ///     for _ in 0..4 {
+    ///         let stage = stage.clone();
+    ///         thread::spawn(move || drop(stage));
+    ///     }
+    ///     // The stages can be waited for on their own.
+    ///     thread::spawn(move || stage.wait().unwrap());
+    /// }
+    /// pipeline.wait().unwrap();
+    /// ```
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn child(&self) -> Rendezvous {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        let child = Rendezvous::with_panic_mode(inner.counters.panic_mode());
+        let parent = Linked::new(self.clone());
+        // Safety: child exist so the ptr is valid
+        unsafe { child.ptr.as_ref() }
+            .links
+            .push(move || drop(parent));
+        child
+    }
+}
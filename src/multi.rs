@@ -0,0 +1,133 @@
+//! Waiting for several independent rendezvous at once.
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::{
+    error::Poisoned,
+    futex::{self, Futex},
+    link::Linked,
+    Arrived, Completion, Rendezvous, WaitResult,
+};
+
+/// Drops all the given references and blocks until all their rendezvous
+/// complete, returning the result of each wait in order.
+///
+/// All the references arrive before waiting, so that none of the rendezvous
+/// waits for the caller to be done with the others, and the caller blocks
+/// only once.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Rendezvous;
+/// use std::thread;
+///
+/// let groups: Vec<_> = (0..3).map(|_| Rendezvous::new()).collect();
+/// for group in &groups {
+///     let worker = group.clone();
+///     thread::spawn(move || drop(worker));
+/// }
+/// for result in rendezvous::wait_all(groups) {
+///     result.unwrap();
+/// }
+/// ```
+///
+/// # Panics
+///
+/// If one of the rendezvous is poisoned and was created with
+/// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+pub fn wait_all<I>(rdvs: I) -> Vec<Result<WaitResult, Poisoned>>
+where
+    I: IntoIterator<Item = Rendezvous>,
+{
+    // Completes once all the rendezvous completed.
+    let all = Rendezvous::new();
+    let arrived: Vec<Arrived> = rdvs
+        .into_iter()
+        .map(|rdv| {
+            let all = Linked::new(all.clone());
+            // Safety: rdv exist so the ptr is valid
+            unsafe { rdv.ptr.as_ref() }.links.push(move || drop(all));
+            rdv.arrive()
+        })
+        .collect();
+    // Cannot be poisoned, as it ignores panics.
+    let _ = all.wait();
+    // The rendezvous are complete, so these do not block.
+    arrived.into_iter().map(Arrived::await_others).collect()
+}
+
+/// Blocks until one of the rendezvous completes, and returns its index.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Rendezvous;
+/// use std::thread;
+///
+/// let (fast, slow) = (Rendezvous::new(), Rendezvous::new());
+/// let completions = [fast.completion(), slow.completion()];
+/// thread::spawn(move || drop(fast));
+/// assert_eq!(rendezvous::wait_any(&completions), 0);
+/// # drop(slow);
+/// ```
+///
+/// # Panics
+///
+/// If `completions` is empty.
+///
+/// # Remarks
+///
+/// - On Linux, the rendezvous are waited for at once with `futex_waitv`.
+///   Elsewhere, each rendezvous notifies the caller on completion, which
+///   costs an allocation per rendezvous until it completes.
+pub fn wait_any(completions: &[Completion]) -> usize {
+    assert!(
+        !completions.is_empty(),
+        "There should be a rendezvous to wait for."
+    );
+    loop {
+        let mut atomics = Vec::with_capacity(completions.len());
+        let mut parked = Vec::with_capacity(completions.len());
+        for (i, completion) in completions.iter().enumerate() {
+            // Safety: completion exist so the ptr is valid
+            let counters = &unsafe { completion.observer.ptr.as_ref() }.counters;
+            let word = counters.futex_word();
+            if word.live == 0 {
+                return i;
+            }
+            atomics.push((word.atomic, word.value));
+            parked.push(counters.park());
+        }
+        if !futex::wait_any(&atomics) {
+            break;
+        }
+    }
+    // Index of the first rendezvous to complete, plus one.
+    let first = Arc::new(AtomicU32::new(0));
+    for (i, completion) in completions.iter().enumerate() {
+        let first = first.clone();
+        // Safety: completion exist so the ptr is valid
+        let inner = unsafe { completion.observer.ptr.as_ref() };
+        inner.links.push(move || {
+            let i = i as u32 + 1;
+            if first
+                .compare_exchange(0, i, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                Futex::wake_all(&*first);
+            }
+        });
+    }
+    loop {
+        match first.load(Ordering::Acquire) {
+            0 => Futex::wait(&first, 0),
+            i => return i as usize - 1,
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Rendezvous spanning several hosts, coordinated over TCP.
+//!
+//! A [`Server`] keeps track of the participants, each [`NetRendezvous`] being
+//! a connection to it. Connecting registers a new participant, and the usual
+//! clone, drop and wait semantics of [`Rendezvous`](crate::Rendezvous) apply
+//! across all the hosts connected to the same server: waiting blocks until
+//! every other connection was either closed or is itself waiting.
+//!
+//! Once all participants are done, the server starts a new round with the
+//! next connection, so that a single server can synchronize successive
+//! phases.
+//!
+//! # Protocol
+//!
+//! The protocol is line based. A client opens a connection and sends `JOIN`,
+//! to which the server replies `OK` once the participant is registered.
+//! Closing the connection drops the participant, while sending `WAIT` makes
+//! the server reply `DONE` once all other participants are done.
+//!
+//! # Remarks
+//!
+//! - The server only notices that a participant is gone once its connection
+//!   is closed or errors. A host which crashes or becomes unreachable without
+//!   the connection being reset stays a participant, and blocks the round,
+//!   until the operating system gives up on the connection, which without
+//!   TCP keepalive enabled system-wide may never happen.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rendezvous::net::{NetRendezvous, Server};
+//!
+//! // On the coordinator.
+//! let server = Server::bind("0.0.0.0:7878")?;
+//! std::thread::spawn(move || server.run());
+//!
+//! // On each host.
+//! let rdv = NetRendezvous::connect("coordinator:7878")?;
+//!
+//! // Do some work.
+//!
+//! // Block until all hosts are done.
+//! rdv.wait()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    fmt::Debug,
+    io::{self, BufRead, BufReader, Write},
+    mem,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+const JOIN: &str = "JOIN";
+const OK: &str = "OK";
+const WAIT: &str = "WAIT";
+const DONE: &str = "DONE";
+
+/// A coordination server for [`NetRendezvous`] clients. See the
+/// [module](self) documentation for more.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    round: Arc<Mutex<Round>>,
+}
+
+/// The participants of the current round.
+#[derive(Debug, Default)]
+struct Round {
+    live: u32,
+    waiting: Vec<TcpStream>,
+}
+
+impl Round {
+    /// Marks a participant as arrived, waiting for the others on `waiter` if
+    /// any, and completes the round if it was the last live one, returning
+    /// the waiters to notify then.
+    ///
+    /// Since this is done under the lock, the participants joining afterwards
+    /// take part in the next round.
+    fn arrive(&mut self, waiter: Option<TcpStream>) -> Vec<TcpStream> {
+        self.waiting.extend(waiter);
+        self.live -= 1;
+        if self.live == 0 {
+            mem::take(&mut self.waiting)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl Server {
+    /// Creates a new server listening on `addr`.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            round: Default::default(),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections and serves them, each on its own thread.
+    ///
+    /// Connections which fail to be accepted are skipped, so this never
+    /// returns.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            // E.g. the client reset the connection before it was accepted.
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let round = Arc::clone(&self.round);
+            thread::spawn(move || serve(stream, &round));
+        }
+        unreachable!("TcpListener::incoming never returns None")
+    }
+}
+
+/// Serves one participant until its connection is closed or it waits.
+fn serve(stream: TcpStream, round: &Mutex<Round>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    if read_line(&mut reader)?.as_deref() != Some(JOIN) {
+        return Err(protocol_error());
+    }
+    round.lock().unwrap().live += 1;
+    // Registered until the end of this function.
+    let mut participant = Participant {
+        round,
+        stream: Some(stream),
+        waits: false,
+    };
+    write_line(participant.stream.as_mut().unwrap(), OK)?;
+    match read_line(&mut reader)?.as_deref() {
+        Some(WAIT) => {
+            participant.wait();
+            Ok(())
+        }
+        None => Ok(()),
+        Some(_) => Err(protocol_error()),
+    }
+}
+
+/// Deregisters a participant when dropped, i.e. when its connection is
+/// closed or errors.
+struct Participant<'a> {
+    round: &'a Mutex<Round>,
+    // Moved to the waiting list when the participant waits.
+    stream: Option<TcpStream>,
+    waits: bool,
+}
+
+impl Participant<'_> {
+    fn wait(mut self) {
+        // Arrives right away, when dropped.
+        self.waits = true;
+    }
+}
+
+impl Drop for Participant<'_> {
+    fn drop(&mut self) {
+        let waiter = self.stream.take().filter(|_| self.waits);
+        let done = self.round.lock().unwrap().arrive(waiter);
+        // Out of the lock, so that a slow waiter does not hold up the others.
+        for mut waiter in done {
+            // Waiters which went away in the meantime do not matter.
+            let _ = write_line(&mut waiter, DONE);
+        }
+    }
+}
+
+/// A participant in a rendezvous coordinated by a [`Server`]. See the
+/// [module](self) documentation for more.
+///
+/// # Remarks
+///
+/// - Unlike [`Rendezvous`](crate::Rendezvous), registering a new participant
+///   can fail, hence [`NetRendezvous`] does not implement [`Clone`] but offers
+///   [`try_clone`](NetRendezvous::try_clone) instead.
+pub struct NetRendezvous {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl NetRendezvous {
+    /// Connects to the server at `addr` and registers a new participant.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        write_line(&mut stream, JOIN)?;
+        expect_line(&mut reader, OK)?;
+        Ok(Self { stream, reader })
+    }
+
+    /// Registers a new participant with the same server.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Self::connect(self.stream.peer_addr()?)
+    }
+
+    /// Drops this participant and waits until all other participants, on
+    /// this and other hosts, are dropped.
+    ///
+    /// The participants connecting once they are all dropped take part in
+    /// the next round and are not waited for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::net::{NetRendezvous, Server};
+    /// use std::thread;
+    ///
+    /// let server = Server::bind("127.0.0.1:0")?;
+    /// let addr = server.local_addr()?;
+    /// thread::spawn(move || server.run());
+    ///
+    /// let rdv = NetRendezvous::connect(addr)?;
+    /// // Connected from another host in practice.
+    /// let other = rdv.try_clone()?;
+    /// thread::spawn(move || drop(other));
+    /// rdv.wait()?;
+    ///
+    /// // Starts a new round.
+    /// NetRendezvous::connect(addr)?.wait()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn wait(mut self) -> io::Result<()> {
+        write_line(&mut self.stream, WAIT)?;
+        expect_line(&mut self.reader, DONE)
+    }
+}
+
+impl Debug for NetRendezvous {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetRendezvous")
+            .field("server", &self.stream.peer_addr().ok())
+            .finish()
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    line.truncate(line.trim_end().len());
+    Ok(Some(line))
+}
+
+fn expect_line(reader: &mut impl BufRead, expected: &str) -> io::Result<()> {
+    match read_line(reader)? {
+        Some(line) if line == expected => Ok(()),
+        Some(_) => Err(protocol_error()),
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+fn write_line(stream: &mut TcpStream, line: &str) -> io::Result<()> {
+    stream.write_all(format!("{line}\n").as_bytes())
+}
+
+fn protocol_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "rendezvous protocol violation")
+}
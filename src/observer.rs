@@ -0,0 +1,170 @@
+//! Handles waiting for a rendezvous without taking part in it.
+
+use core::{fmt, ptr::NonNull};
+
+use crate::{release, RDVInner, Rendezvous};
+
+/// A handle waiting for the participants of a [`Rendezvous`] without being
+/// one, created by [`Rendezvous::observer`].
+///
+/// Observers keep the rendezvous allocated but never delay its completion.
+pub struct Observer {
+    pub(crate) ptr: NonNull<RDVInner>,
+}
+
+impl Rendezvous {
+    /// Returns an [`Observer`] of this rendezvous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let observer = rdv.observer();
+    /// thread::spawn(move || {
+    ///     // Do some work.
+    ///     drop(rdv);
+    /// });
+    /// observer.wait().unwrap();
+    /// ```
+    pub fn observer(&self) -> Observer {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        Observer { ptr: self.ptr }
+    }
+
+    /// Drops this reference, as far as the rendezvous is concerned, but
+    /// keeps observing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let observer = rdv.detach();
+    /// // Returns immediately, as there is no reference left.
+    /// observer.wait().unwrap();
+    /// ```
+    pub fn detach(self) -> Observer {
+        let ptr = self.into_ptr();
+        // Safety: we forgot self so we own its allocation dependency, which
+        // is handed over to the observer.
+        unsafe { ptr.as_ref() }.arrive();
+        Observer { ptr }
+    }
+}
+
+impl Observer {
+    /// Returns whether all the references to the rendezvous are dropped.
+    ///
+    /// Once it returns `true`, [`wait`](Observer::wait) does not block.
+    pub fn is_complete(&self) -> bool {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.counters.live() == 0
+    }
+
+    /// Blocks until all the references to the rendezvous are dropped.
+    ///
+    /// Unlike [`Rendezvous::wait`], this can be called any number of times
+    /// and observers are never the [leader](crate::WaitResult::is_leader).
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait(&self) -> Result<(), crate::error::Poisoned> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        let counters = &inner.counters;
+        crate::hooks::wait(inner, counters.live());
+        crate::report(inner.check_poisoned(), counters.panic_mode())
+    }
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        // Safety: self owns an allocation dependency it does not use
+        // afterwards.
+        unsafe { release(self.ptr) };
+    }
+}
+
+impl Clone for Observer {
+    fn clone(&self) -> Self {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        Self { ptr: self.ptr }
+    }
+}
+
+// Safety: observers only read the counters, which are atomic.
+unsafe impl Send for Observer {}
+// Safety: same as above.
+unsafe impl Sync for Observer {}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        f.debug_struct("Observer")
+            .field("live barriers", &inner.counters.live())
+            .field("poisoned", &inner.counters.is_poisoned())
+            .finish()
+    }
+}
+
+/// A token which becomes ready once all the references to a [`Rendezvous`]
+/// are dropped, created by [`Rendezvous::completion`].
+///
+/// It is an [`Observer`] which can also be polled, e.g. from a loop doing
+/// other work in the meantime.
+#[derive(Clone, Debug)]
+pub struct Completion {
+    pub(crate) observer: Observer,
+}
+
+impl Rendezvous {
+    /// Returns a [`Completion`] of this rendezvous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let completion = rdv.completion();
+    /// assert!(!completion.is_complete());
+    /// drop(rdv);
+    /// assert!(completion.is_complete());
+    /// completion.wait().unwrap();
+    /// completion.wait().unwrap();
+    /// ```
+    pub fn completion(&self) -> Completion {
+        Completion {
+            observer: self.observer(),
+        }
+    }
+}
+
+impl Completion {
+    /// Returns whether all the references to the rendezvous are dropped.
+    ///
+    /// Once it returns `true`, [`wait`](Completion::wait) does not block.
+    pub fn is_complete(&self) -> bool {
+        self.observer.is_complete()
+    }
+
+    /// Blocks until all the references to the rendezvous are dropped, see
+    /// [`Observer::wait`].
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait(&self) -> Result<(), crate::error::Poisoned> {
+        self.observer.wait()
+    }
+}
@@ -0,0 +1,94 @@
+//! Parallel loops joined by a rendezvous.
+
+use std::{num::NonZeroUsize, ops::Range, sync::Arc, thread};
+
+use crate::Rendezvous;
+
+/// Splits `range` in chunks, runs `f` on each of them in parallel and returns
+/// once all chunks are done.
+///
+/// The range is split in as many chunks as there are available CPUs. One
+/// chunk runs on the calling thread while the others each run on a newly
+/// spawned thread.
+///
+/// # Remarks
+///
+/// - A panic in a chunk running on a spawned thread does not propagate to
+///   the caller, who only waits for the other chunks to end.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicU64, Ordering},
+///     Arc,
+/// };
+///
+/// let sum = Arc::new(AtomicU64::new(0));
+/// let total = Arc::clone(&sum);
+/// rendezvous::parallel_for(0..1000, move |chunk| {
+///     let partial: u64 = chunk.map(|i| i as u64).sum();
+///     total.fetch_add(partial, Ordering::Relaxed);
+/// });
+/// assert_eq!(sum.load(Ordering::Relaxed), 499_500);
+/// # std::thread::sleep(std::time::Duration::from_millis(500)); // wait for background threads closed: https://github.com/rust-lang/miri/issues/1371
+/// ```
+pub fn parallel_for<F>(range: Range<usize>, f: F)
+where
+    F: Fn(Range<usize>) + Send + Sync + 'static,
+{
+    let chunks = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    parallel_for_with(
+        range,
+        chunks,
+        |job| {
+            thread::spawn(job);
+        },
+        f,
+    );
+}
+
+/// Splits `range` in `chunks` chunks, runs `f` on each of them in parallel
+/// and returns once all chunks are done.
+///
+/// One chunk runs on the calling thread while the others are each handed
+/// to `spawn`, which must run them to completion, for instance on the
+/// threads of an existing pool.
+///
+/// # Remarks
+///
+/// - A panic in a chunk running outside of the calling thread does not
+///   propagate to the caller, who only waits for the other chunks to end.
+pub fn parallel_for_with<F, S>(range: Range<usize>, chunks: usize, mut spawn: S, f: F)
+where
+    F: Fn(Range<usize>) + Send + Sync + 'static,
+    S: FnMut(Box<dyn FnOnce() + Send>),
+{
+    let f = Arc::new(f);
+    let rdv = Rendezvous::new();
+    let mut chunks = split(range, chunks);
+    let first = chunks.next();
+    for chunk in chunks {
+        let f = Arc::clone(&f);
+        let rdv = rdv.clone();
+        spawn(Box::new(move || {
+            f(chunk);
+            drop(rdv);
+        }));
+    }
+    if let Some(chunk) = first {
+        f(chunk);
+    }
+    // Not poisoning, as panics are not propagated.
+    let _ = rdv.wait();
+}
+
+/// Splits `range` in at most `n` non-empty chunks whose lengths differ by at
+/// most one.
+fn split(range: Range<usize>, n: usize) -> impl Iterator<Item = Range<usize>> {
+    let n = n.max(1);
+    let len = range.len();
+    let (base, rem) = (len / n, len % n);
+    let bound = move |i: usize| range.start + i * base + i.min(rem);
+    (0..n.min(len)).map(move |i| bound(i)..bound(i + 1))
+}
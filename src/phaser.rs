@@ -0,0 +1,235 @@
+//! A multi-phase synchronizer with dynamic registration, after Java's
+//! `Phaser`.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::Futex;
+
+/// A reusable barrier whose parties register and deregister dynamically.
+///
+/// Each phase completes once all the registered parties arrived, which
+/// starts the next phase. Unlike with [`Rendezvous`](crate::Rendezvous),
+/// parties are counted explicitly rather than by handles, so a phaser is
+/// typically shared through a reference or an [`Arc`](std::sync::Arc).
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Phaser;
+/// use std::thread;
+///
+/// let phaser = Phaser::new(1);
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         phaser.register();
+///         s.spawn(|| {
+///             for _ in 0..3 {
+///                 // Do the work of this phase.
+///                 phaser.arrive_and_await();
+///             }
+///             phaser.arrive_and_deregister();
+///         });
+///     }
+///     // Let the workers run the phases without us.
+///     phaser.arrive_and_deregister();
+/// });
+/// assert_eq!(phaser.phase(), 3);
+/// ```
+///
+/// # Remarks
+///
+/// - There cannot be more than 2²⁴ - 1 parties on a single phaser.
+/// - Phase numbers wrap around.
+pub struct Phaser {
+    // From the least significant bits: the current phase modulo 2¹⁶, the
+    // number of parties which arrived in it and the number of parties.
+    state: AtomicU64,
+    // Bumped after each phase, for the waiters to block on.
+    phase: AtomicU32,
+}
+
+const TAG_BITS: u32 = 16;
+const COUNT_BITS: u32 = 24;
+const MAX_PARTIES: u32 = (1 << COUNT_BITS) - 1;
+const PARTIES_SHIFT: u32 = TAG_BITS + COUNT_BITS;
+const ONE_PARTY: u64 = 1 << PARTIES_SHIFT;
+
+fn parties(state: u64) -> u32 {
+    (state >> PARTIES_SHIFT) as u32
+}
+
+fn arrived(state: u64) -> u32 {
+    (state >> TAG_BITS) as u32 & MAX_PARTIES
+}
+
+fn tag(state: u64) -> u16 {
+    state as u16
+}
+
+/// Returns the state following the arrival of a party, optionally
+/// deregistering it, and whether this completes the phase, or `None` if
+/// all the parties arrived already.
+fn next(state: u64, deregister: bool) -> Option<(u64, bool)> {
+    let (parties, arrived) = (parties(state), arrived(state));
+    if arrived >= parties {
+        return None;
+    }
+    let (parties, arrived) = if deregister {
+        (parties - 1, arrived)
+    } else {
+        (parties, arrived + 1)
+    };
+    let advance = parties > 0 && arrived == parties;
+    let (arrived, tag) = if advance {
+        (0, tag(state).wrapping_add(1))
+    } else {
+        (arrived, tag(state))
+    };
+    Some((
+        u64::from(parties) << PARTIES_SHIFT | u64::from(arrived) << TAG_BITS | u64::from(tag),
+        advance,
+    ))
+}
+
+impl Phaser {
+    /// Creates a new phaser with `parties` registered parties, in phase 0.
+    ///
+    /// # Panics
+    ///
+    /// If `parties` is 2²⁴ or more.
+    pub const fn new(parties: u32) -> Self {
+        assert!(
+            parties <= MAX_PARTIES,
+            "There should not be more than 2^24 - 1 parties of one Phaser."
+        );
+        Self {
+            state: AtomicU64::new((parties as u64) << PARTIES_SHIFT),
+            phase: AtomicU32::new(0),
+        }
+    }
+
+    /// Registers a new party, which takes part in the current phase, and
+    /// returns the current phase.
+    ///
+    /// # Panics
+    ///
+    /// If there are already 2²⁴ - 1 parties.
+    pub fn register(&self) -> u32 {
+        let prev = self
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                (parties(s) < MAX_PARTIES).then_some(s + ONE_PARTY)
+            })
+            .expect("There should not be more than 2^24 - 1 parties of one Phaser.");
+        self.phase_of(tag(prev))
+    }
+
+    /// Arrives in the current phase without waiting for the other parties,
+    /// and returns the phase arrived in.
+    ///
+    /// # Panics
+    ///
+    /// If all the registered parties arrived already.
+    pub fn arrive(&self) -> u32 {
+        self.arrive_as(false).0
+    }
+
+    /// Arrives in the current phase and deregisters, without waiting for the
+    /// other parties, and returns the phase arrived in.
+    ///
+    /// # Panics
+    ///
+    /// If all the registered parties arrived already.
+    pub fn arrive_and_deregister(&self) -> u32 {
+        self.arrive_as(true).0
+    }
+
+    /// Arrives in the current phase and blocks until all the other parties
+    /// arrived too, and returns the phase arrived in.
+    ///
+    /// # Panics
+    ///
+    /// If all the registered parties arrived already.
+    pub fn arrive_and_await(&self) -> u32 {
+        self.arrive_and_await_advance().0
+    }
+
+    /// Same as [`Phaser::arrive_and_await`], also returning whether this
+    /// arrival completed the phase.
+    pub(crate) fn arrive_and_await_advance(&self) -> (u32, bool) {
+        let (phase, advanced) = self.arrive_as(false);
+        if !advanced {
+            self.await_advance(phase);
+        }
+        (phase, advanced)
+    }
+
+    /// Blocks until the phaser is past `phase`, and returns the current
+    /// phase.
+    pub fn await_advance(&self, phase: u32) -> u32 {
+        loop {
+            let current = self.phase();
+            // Phases still to be published compare as not past yet.
+            if (current.wrapping_sub(phase) as i32) > 0 {
+                return current;
+            }
+            Futex::wait(&self.phase, current);
+        }
+    }
+
+    /// Returns the current phase.
+    pub fn phase(&self) -> u32 {
+        self.phase.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of registered parties.
+    pub fn parties(&self) -> u32 {
+        parties(self.state.load(Ordering::Relaxed))
+    }
+
+    /// Returns the number of parties which arrived in the current phase.
+    pub fn arrived(&self) -> u32 {
+        arrived(self.state.load(Ordering::Relaxed))
+    }
+
+    /// Returns the phase whose tag in the state is `tag`, which may not be
+    /// published yet or may already be over.
+    fn phase_of(&self, tag: u16) -> u32 {
+        let current = self.phase();
+        let ahead = tag.wrapping_sub(current as u16) as i16;
+        current.wrapping_add_signed(ahead.into())
+    }
+
+    fn arrive_as(&self, deregister: bool) -> (u32, bool) {
+        let prev = self
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |s| {
+                next(s, deregister).map(|(s, _)| s)
+            })
+            .expect("There should not be more arrivals than parties on a Phaser.");
+        let advanced = next(prev, deregister).is_some_and(|(_, advance)| advance);
+        // Resolved before publishing our own advance, if any.
+        let phase = self.phase_of(tag(prev));
+        if advanced {
+            self.phase.fetch_add(1, Ordering::Release);
+            Futex::wake_all(&self.phase);
+        }
+        (phase, advanced)
+    }
+}
+
+impl fmt::Debug for Phaser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.load(Ordering::Relaxed);
+        f.debug_struct("Phaser")
+            .field("phase", &self.phase())
+            .field("parties", &parties(state))
+            .field("arrived", &arrived(state))
+            .finish()
+    }
+}
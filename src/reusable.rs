@@ -0,0 +1,97 @@
+//! A rendezvous which synchronizes its handles repeatedly.
+
+use std::{fmt, sync::Arc};
+
+use crate::Phaser;
+
+/// A rendezvous whose handles wait for each other repeatedly, once per
+/// generation, like a [`Barrier`](std::sync::Barrier) whose number of
+/// threads is given by the number of handles.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::ReusableRendezvous;
+/// use std::thread;
+///
+/// let rdv = ReusableRendezvous::new();
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let rdv = rdv.clone();
+///         thread::spawn(move || {
+///             for _tick in 0..3 {
+///                 // Do the work of this tick.
+///                 rdv.wait();
+///             }
+///         })
+///     })
+///     .collect();
+/// // This handle does not take part in the ticks.
+/// drop(rdv);
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// ```
+///
+/// # Remarks
+///
+/// - There cannot be more than 2²⁴ - 1 simultaneous copies of a single
+///   rendezvous.
+pub struct ReusableRendezvous {
+    // One party per handle.
+    phaser: Arc<Phaser>,
+}
+
+impl ReusableRendezvous {
+    /// Creates a new `ReusableRendezvous`. Clone it so that other threads can
+    /// synchronize on it.
+    pub fn new() -> Self {
+        Self {
+            phaser: Arc::new(Phaser::new(1)),
+        }
+    }
+
+    /// Blocks until all the handles waited in the current generation, or
+    /// were dropped. Returns whether this handle was the last one to wait,
+    /// which started the next generation.
+    pub fn wait(&self) -> bool {
+        self.phaser.arrive_and_await_advance().1
+    }
+
+    /// Returns the number of generations completed so far, wrapping around.
+    pub fn generation(&self) -> u32 {
+        self.phaser.phase()
+    }
+}
+
+impl Drop for ReusableRendezvous {
+    fn drop(&mut self) {
+        // The others may only have been waiting for us.
+        self.phaser.arrive_and_deregister();
+    }
+}
+
+impl Clone for ReusableRendezvous {
+    fn clone(&self) -> Self {
+        self.phaser.register();
+        Self {
+            phaser: self.phaser.clone(),
+        }
+    }
+}
+
+impl Default for ReusableRendezvous {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ReusableRendezvous {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReusableRendezvous")
+            .field("handles", &self.phaser.parties())
+            .field("arrived", &self.phaser.arrived())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
@@ -0,0 +1,67 @@
+//! Typed roles for the common case where workers drop their references and
+//! a coordinator waits.
+
+use crate::Rendezvous;
+
+/// A reference to a [`Rendezvous`] which can only be cloned and dropped,
+/// created by [`Rendezvous::split`].
+#[derive(Clone, Debug)]
+pub struct Participant {
+    _rdv: Rendezvous,
+}
+
+/// A reference to a [`Rendezvous`] which waits for the [`Participant`]s,
+/// created by [`Rendezvous::split`].
+#[derive(Debug)]
+pub struct Waiter {
+    rdv: Rendezvous,
+}
+
+impl Rendezvous {
+    /// Splits this reference into a [`Participant`], to be cloned and handed
+    /// out to the workers, and a [`Waiter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let (participant, waiter) = Rendezvous::new().split();
+    /// for _ in 0..4 {
+    ///     let participant = participant.clone();
+    ///     thread::spawn(move || {
+    ///         // Do some work.
+    ///         drop(participant);
+    ///     });
+    /// }
+    /// drop(participant);
+    /// waiter.wait().unwrap();
+    /// ```
+    pub fn split(self) -> (Participant, Waiter) {
+        (Participant { _rdv: self.clone() }, Waiter { rdv: self })
+    }
+}
+
+impl Waiter {
+    /// Returns a new [`Participant`] of the rendezvous.
+    pub fn participant(&self) -> Participant {
+        Participant {
+            _rdv: self.rdv.clone(),
+        }
+    }
+
+    /// Waits until all the participants are dropped, see
+    /// [`Rendezvous::wait`].
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait(self) -> Result<crate::WaitResult, crate::error::Poisoned> {
+        self.rdv.wait()
+    }
+
+    /// Waits asynchronously until all the participants are dropped, see
+    /// [`Rendezvous::wait_async`].
+    #[cfg(any(feature = "async", all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    pub fn wait_async(self) -> crate::future::WaitFuture {
+        self.rdv.wait_async()
+    }
+}
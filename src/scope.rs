@@ -0,0 +1,150 @@
+//! Scopes waiting for the threads spawned in them.
+
+use std::{fmt, thread};
+
+use crate::{error::Poisoned, PanicMode, Rendezvous, WaitResult};
+
+/// Runs `f`, then waits for all the threads it spawned with
+/// [`Scope::spawn`] to end.
+///
+/// Unlike [`std::thread::scope`], the threads cannot borrow from the
+/// caller, but they are detached: their [`JoinHandle`](thread::JoinHandle)s
+/// can be dropped or outlive the scope.
+///
+/// # Panics
+///
+/// If one of the spawned threads panicked.
+///
+/// # Remarks
+///
+/// - If `f` panics, the scope does not wait for the threads.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicU32, Ordering},
+///     Arc,
+/// };
+///
+/// let done = Arc::new(AtomicU32::new(0));
+/// rendezvous::scope(|s| {
+///     for _ in 0..4 {
+///         let done = Arc::clone(&done);
+///         s.spawn(move || done.fetch_add(1, Ordering::Relaxed));
+///     }
+/// });
+/// assert_eq!(done.load(Ordering::Relaxed), 4);
+/// ```
+pub fn scope<F, R>(f: F) -> R
+where
+    F: FnOnce(&Scope) -> R,
+{
+    let scope = Scope {
+        rdv: Rendezvous::with_panic_mode(PanicMode::Propagate),
+    };
+    let result = f(&scope);
+    let _ = scope.rdv.wait();
+    result
+}
+
+/// A scope to spawn threads in, see [`scope`].
+pub struct Scope {
+    rdv: Rendezvous,
+}
+
+impl Scope {
+    /// Spawns a thread running `f`, which the scope waits for.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn spawn<F, T>(&self, f: F) -> thread::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.rdv.spawn(f)
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope").field("rdv", &self.rdv).finish()
+    }
+}
+
+/// A rendezvous between some of the threads of a [`std::thread::scope`],
+/// marking the end of a phase before the end of the scope.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::ScopedRendezvous;
+/// use std::{sync::Mutex, thread};
+///
+/// let loaded = Mutex::new(Vec::new());
+/// thread::scope(|s| {
+///     let loading = ScopedRendezvous::new(s);
+///     for i in 0..4 {
+///         let loaded = &loaded;
+///         loading.spawn(move || loaded.lock().unwrap().push(i));
+///     }
+///     // The loaders are done, the scope is not.
+///     loading.wait().unwrap();
+///     assert_eq!(loaded.lock().unwrap().len(), 4);
+/// });
+/// ```
+pub struct ScopedRendezvous<'scope, 'env> {
+    scope: &'scope thread::Scope<'scope, 'env>,
+    rdv: Rendezvous,
+}
+
+impl<'scope, 'env> ScopedRendezvous<'scope, 'env> {
+    /// Creates a new rendezvous between threads of `scope`.
+    pub fn new(scope: &'scope thread::Scope<'scope, 'env>) -> Self {
+        Self {
+            scope,
+            rdv: Rendezvous::new(),
+        }
+    }
+
+    /// Spawns a scoped thread running `f` while holding a new reference to
+    /// the rendezvous, dropped once `f` returns or panics.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn spawn<F, T>(&self, f: F) -> thread::ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let rdv = self.rdv.clone();
+        self.scope.spawn(move || {
+            // Dropped after f, even if it panics.
+            let _rdv = rdv.adopt();
+            f()
+        })
+    }
+
+    /// Returns the rendezvous, which other participants can join by cloning
+    /// it.
+    pub fn rendezvous(&self) -> &Rendezvous {
+        &self.rdv
+    }
+
+    /// Waits until the threads spawned with
+    /// [`spawn`](ScopedRendezvous::spawn), and the other references to the
+    /// rendezvous, are done, see [`Rendezvous::wait`].
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`].
+    pub fn wait(self) -> Result<WaitResult, Poisoned> {
+        self.rdv.wait()
+    }
+}
+
+impl fmt::Debug for ScopedRendezvous<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedRendezvous")
+            .field("rdv", &self.rdv)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,88 @@
+//! A counting semaphore on the futex layer.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::{self, Futex};
+
+/// A counting semaphore, whose permits are acquired one at a time and
+/// released any number at a time.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::Semaphore;
+/// use std::thread;
+///
+/// // At most two workers at a time.
+/// let semaphore = Semaphore::new(2);
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             semaphore.acquire();
+///             // Do some work.
+///             semaphore.release(1);
+///         });
+///     }
+/// });
+/// assert_eq!(semaphore.available(), 2);
+/// ```
+pub struct Semaphore {
+    permits: AtomicU32,
+}
+
+impl Semaphore {
+    /// Creates a semaphore with `permits` available permits.
+    pub const fn new(permits: u32) -> Self {
+        Self {
+            permits: AtomicU32::new(permits),
+        }
+    }
+
+    /// Blocks until a permit is available, and acquires it.
+    pub fn acquire(&self) {
+        while !self.try_acquire() {
+            Futex::wait(&self.permits, 0);
+        }
+    }
+
+    /// Acquires a permit if one is available, and returns whether it did.
+    pub fn try_acquire(&self) -> bool {
+        self.permits
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |p| p.checked_sub(1))
+            .is_ok()
+    }
+
+    /// Releases `n` permits, waking up as many blocked threads.
+    ///
+    /// # Panics
+    ///
+    /// If there would be more than 2³² - 1 available permits.
+    pub fn release(&self, n: u32) {
+        self.permits
+            .fetch_update(Ordering::Release, Ordering::Relaxed, |p| p.checked_add(n))
+            .expect("There should not be more than 2^32 - 1 permits on a Semaphore.");
+        match n {
+            0 => {}
+            1 => futex::wake_one(&self.permits),
+            _ => Futex::wake_all(&self.permits),
+        }
+    }
+
+    /// Returns the number of available permits.
+    pub fn available(&self) -> u32 {
+        self.permits.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("available", &self.available())
+            .finish()
+    }
+}
@@ -0,0 +1,156 @@
+//! A collection of rendezvous identified by keys.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{error::Poisoned, Completion, Rendezvous, WeakRendezvous};
+
+/// A collection of rendezvous identified by keys, e.g. in-flight batches.
+///
+/// The set only observes its rendezvous, so it never delays their
+/// completion. Completed rendezvous are kept until [pruned](RendezvousSet::prune)
+/// or [removed](RendezvousSet::remove).
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::RendezvousSet;
+/// use std::thread;
+///
+/// let batches = RendezvousSet::new();
+/// for id in 0..3 {
+///     let batch = batches.insert(id);
+///     for _ in 0..4 {
+///         let batch = batch.clone();
+///         thread::spawn(move || drop(batch));
+///     }
+/// }
+/// batches.wait(&1).unwrap().unwrap();
+/// batches.wait_all().unwrap();
+/// assert!(batches.incomplete().is_empty());
+/// ```
+pub struct RendezvousSet<K> {
+    groups: Mutex<HashMap<K, Group>>,
+}
+
+struct Group {
+    weak: WeakRendezvous,
+    completion: Completion,
+}
+
+impl<K: Eq + Hash + Clone> RendezvousSet<K> {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self {
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, Group>> {
+        // The map is consistent even if a thread panicked.
+        self.groups.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Creates a new rendezvous identified by `key`, replacing any previous
+    /// one, and returns its first reference.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn insert(&self, key: K) -> Rendezvous {
+        let rdv = Rendezvous::new();
+        let group = Group {
+            weak: rdv.downgrade(),
+            completion: rdv.completion(),
+        };
+        self.lock().insert(key, group);
+        rdv
+    }
+
+    /// Returns a new reference to the rendezvous identified by `key`, or
+    /// `None` if there is none or if it completed already.
+    pub fn get(&self, key: &K) -> Option<Rendezvous> {
+        self.lock().get(key)?.weak.upgrade()
+    }
+
+    /// Blocks until the rendezvous identified by `key` completes, or returns
+    /// `None` if there is none.
+    ///
+    /// # Panics
+    ///
+    /// If the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    pub fn wait(&self, key: &K) -> Option<Result<(), Poisoned>> {
+        let completion = self.lock().get(key)?.completion.clone();
+        Some(completion.wait())
+    }
+
+    /// Blocks until all the rendezvous of the set complete, returning the
+    /// first error if some are poisoned.
+    ///
+    /// The rendezvous inserted meanwhile are not waited for.
+    ///
+    /// # Panics
+    ///
+    /// If one of the rendezvous is poisoned and was created with
+    /// [`PanicMode::Propagate`](crate::PanicMode::Propagate).
+    pub fn wait_all(&self) -> Result<(), Poisoned> {
+        let completions: Vec<_> = self
+            .lock()
+            .values()
+            .map(|group| group.completion.clone())
+            .collect();
+        // Observers, so waiting one after the other does not delay the
+        // others.
+        let mut result = Ok(());
+        for completion in completions {
+            result = result.and(completion.wait());
+        }
+        result
+    }
+
+    /// Returns the keys of the rendezvous which did not complete yet.
+    pub fn incomplete(&self) -> Vec<K> {
+        self.lock()
+            .iter()
+            .filter(|(_, group)| !group.completion.is_complete())
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Removes the rendezvous identified by `key`, returning whether there
+    /// was one. Its references are not affected.
+    pub fn remove(&self, key: &K) -> bool {
+        self.lock().remove(key).is_some()
+    }
+
+    /// Removes the completed rendezvous.
+    pub fn prune(&self) {
+        self.lock()
+            .retain(|_, group| !group.completion.is_complete());
+    }
+
+    /// Returns the number of rendezvous in the set, completed or not.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for RendezvousSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for RendezvousSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups = self.groups.lock().unwrap_or_else(PoisonError::into_inner);
+        f.debug_set().entries(groups.keys()).finish()
+    }
+}
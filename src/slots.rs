@@ -0,0 +1,110 @@
+//! Participants registered ahead of their references.
+
+use core::{fmt, ptr::NonNull};
+
+use crate::{release_n, Count, RDVInner, Rendezvous};
+
+/// Participants registered to a [`Rendezvous`] whose references are handed
+/// out later, created by [`Rendezvous::add`].
+///
+/// The rendezvous cannot complete while slots remain. Dropping the slots
+/// arrives for all the remaining ones.
+pub struct Slots {
+    // Dangling once there is no remaining slot.
+    ptr: NonNull<RDVInner>,
+    remaining: Count,
+}
+
+impl Rendezvous {
+    /// Registers `n` participants at once, whose references are taken from
+    /// the returned [`Slots`] later, e.g. once their workers are spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    /// use std::thread;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let mut slots = rdv.add(4);
+    /// thread::spawn(move || {
+    ///     // Spawn the workers lazily, the rendezvous waits for them anyway.
+    ///     while let Some(worker) = slots.take_slot() {
+    ///         thread::spawn(move || drop(worker));
+    ///     }
+    /// });
+    /// rdv.wait().unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If there would be more than 2³² - 1 copies of the rendezvous.
+    pub fn add(&self, n: Count) -> Slots {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.register_n(n);
+        crate::hooks::registered(inner, n);
+        Slots {
+            ptr: self.ptr,
+            remaining: n,
+        }
+    }
+}
+
+impl Slots {
+    /// Returns the reference of one of the remaining participants, if any.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn take_slot(&mut self) -> Option<Rendezvous> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        Some(Rendezvous::from_ptr(self.ptr))
+    }
+
+    /// Returns the number of remaining participants.
+    pub fn remaining(&self) -> Count {
+        self.remaining
+    }
+}
+
+impl Iterator for Slots {
+    type Item = Rendezvous;
+
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn next(&mut self) -> Option<Rendezvous> {
+        self.take_slot()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl Drop for Slots {
+    fn drop(&mut self) {
+        if self.remaining == 0 {
+            return;
+        }
+        // Safety: the remaining slots own a live handle and an allocation
+        // dependency each, that we give up.
+        let inner = unsafe { self.ptr.as_ref() };
+        if crate::panicking() {
+            inner.counters.record_panic();
+        }
+        inner.arrive_n(self.remaining);
+        // Safety: see above.
+        unsafe { release_n(self.ptr, self.remaining) };
+    }
+}
+
+// Safety: slots are handed out as references, which are Send and Sync.
+unsafe impl Send for Slots {}
+// Safety: same as above.
+unsafe impl Sync for Slots {}
+
+impl fmt::Debug for Slots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slots")
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
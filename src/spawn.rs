@@ -0,0 +1,123 @@
+//! Threads holding a reference to a rendezvous.
+
+use std::{io, thread};
+
+use crate::Rendezvous;
+
+impl Rendezvous {
+    /// Spawns a thread running `f` while holding a new reference to the
+    /// rendezvous, dropped once `f` returns or panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// for i in 0..4 {
+    ///     rdv.spawn(move || println!("worker {i}"));
+    /// }
+    /// rdv.wait().unwrap();
+    /// ```
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn spawn<F, T>(&self, f: F) -> thread::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        thread::spawn(self.guard(f))
+    }
+
+    /// Wraps `f` so that it holds a new reference while running.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub(crate) fn guard<F, T>(&self, f: F) -> impl FnOnce() -> T + Send + 'static
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let rdv = self.clone();
+        move || {
+            // Dropped after f, even if it panics.
+            let _rdv = rdv.adopt();
+            f()
+        }
+    }
+}
+
+/// Spawns threads from a [`thread::Builder`] while holding a reference to a
+/// rendezvous.
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::{BuilderExt, Rendezvous};
+/// use std::thread;
+///
+/// let rdv = Rendezvous::new();
+/// thread::Builder::new()
+///     .name("worker".into())
+///     .stack_size(64 * 1024)
+///     .spawn_with(&rdv, || assert_eq!(thread::current().name(), Some("worker")))
+///     .unwrap();
+/// rdv.wait().unwrap();
+/// ```
+pub trait BuilderExt {
+    /// Spawns a thread running `f` while holding a new reference to `rdv`,
+    /// dropped once `f` returns or panics.
+    ///
+    /// # Errors
+    ///
+    /// If the thread could not be spawned, in which case the reference is
+    /// dropped right away.
+    fn spawn_with<F, T>(self, rdv: &Rendezvous, f: F) -> io::Result<thread::JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static;
+}
+
+impl BuilderExt for thread::Builder {
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    fn spawn_with<F, T>(self, rdv: &Rendezvous, f: F) -> io::Result<thread::JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.spawn(rdv.guard(f))
+    }
+}
+
+/// Spawns a thread holding a new reference to a rendezvous, optionally
+/// naming it.
+///
+/// `spawn!(rdv => f)` is [`rdv.spawn(f)`](Rendezvous::spawn) and
+/// `spawn!(rdv, name => f)` spawns with a [`thread::Builder`] named `name`.
+///
+/// # Panics
+///
+/// If the thread could not be spawned, like [`thread::spawn`].
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::{spawn, Rendezvous};
+///
+/// let rdv = Rendezvous::new();
+/// for i in 0..4 {
+///     spawn!(rdv, format!("worker-{i}") => move || println!("worker {i}"));
+/// }
+/// spawn!(rdv => || println!("anonymous"));
+/// rdv.wait().unwrap();
+/// ```
+#[macro_export]
+macro_rules! spawn {
+    ($rdv:expr => $f:expr) => {
+        $crate::Rendezvous::spawn(&$rdv, $f)
+    };
+    ($rdv:expr, $name:expr => $f:expr) => {
+        $crate::BuilderExt::spawn_with(
+            ::std::thread::Builder::new().name(::std::string::String::from($name)),
+            &$rdv,
+            $f,
+        )
+        .expect("failed to spawn thread")
+    };
+}
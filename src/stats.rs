@@ -0,0 +1,211 @@
+//! Registration statistics of each rendezvous, for capacity planning, and
+//! statistics of each wait, for performance tuning.
+
+#[cfg(not(feature = "u64-counters"))]
+use std::sync::atomic::AtomicU32;
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+use std::{cell::Cell, time::Instant};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{Count, Observer, RDVInner, Rendezvous, WaitResult};
+
+/// The atomic holding a [`Count`].
+#[cfg(not(feature = "u64-counters"))]
+type AtomicCount = AtomicU32;
+#[cfg(feature = "u64-counters")]
+type AtomicCount = AtomicU64;
+
+/// The statistics kept in the inner of each rendezvous.
+#[derive(Default)]
+pub(crate) struct Stats {
+    registered: AtomicU64,
+    max_live: AtomicCount,
+}
+
+/// Records that `n` references were registered on `inner`.
+// Count is already u64 with the u64-counters feature.
+#[allow(clippy::useless_conversion)]
+pub(crate) fn registered(inner: &RDVInner, n: Count) {
+    inner
+        .stats
+        .registered
+        .fetch_add(u64::from(n), Ordering::Relaxed);
+    inner
+        .stats
+        .max_live
+        .fetch_max(inner.counters.live(), Ordering::Relaxed);
+}
+
+impl RDVInner {
+    fn total_registered(&self) -> u64 {
+        self.stats.registered.load(Ordering::Relaxed)
+    }
+
+    fn max_live(&self) -> Count {
+        self.stats.max_live.load(Ordering::Relaxed)
+    }
+}
+
+impl Rendezvous {
+    /// Returns the number of references ever registered on this rendezvous,
+    /// including the first ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// for _ in 0..3 {
+    ///     drop(rdv.clone());
+    /// }
+    /// assert_eq!(rdv.total_registered(), 4);
+    /// assert_eq!(rdv.max_live(), 2);
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `stats` feature.
+    pub fn total_registered(&self) -> u64 {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.total_registered()
+    }
+
+    /// Returns the highest number of live references this rendezvous had.
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `stats` feature.
+    /// - The live count is sampled right after each registration, so that
+    ///   references dropped concurrently may be missed.
+    pub fn max_live(&self) -> Count {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.max_live()
+    }
+}
+
+impl Observer {
+    /// Returns the number of references ever registered on the observed
+    /// rendezvous, see [`Rendezvous::total_registered`].
+    pub fn total_registered(&self) -> u64 {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.total_registered()
+    }
+
+    /// Returns the highest number of live references the observed
+    /// rendezvous had, see [`Rendezvous::max_live`].
+    pub fn max_live(&self) -> Count {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.max_live()
+    }
+}
+
+/// Statistics of a blocking wait, for measuring the synchronization overhead,
+/// see [`WaitResult::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WaitStats {
+    blocked: Duration,
+    futex_waits: u32,
+    fast_path: bool,
+}
+
+impl WaitStats {
+    /// Returns how long the wait blocked for the other references, zero on
+    /// the [fast path](WaitStats::is_fast_path).
+    pub fn blocked(&self) -> Duration {
+        self.blocked
+    }
+
+    /// Returns the number of calls to the futex of the platform made while
+    /// blocked, including the ones which returned right away or spuriously.
+    pub fn futex_waits(&self) -> u32 {
+        self.futex_waits
+    }
+
+    /// Returns whether the wait returned without blocking, all the other
+    /// references having arrived already.
+    pub fn is_fast_path(&self) -> bool {
+        self.fast_path
+    }
+}
+
+impl WaitResult {
+    /// Returns the statistics of the wait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// use std::{thread, time::Duration};
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let worker = rdv.clone();
+    /// let worker = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(10));
+    ///     worker.wait().unwrap().stats()
+    /// });
+    /// let stats = rdv.wait().unwrap().stats();
+    /// assert!(!stats.is_fast_path());
+    /// assert!(stats.blocked() >= Duration::from_millis(5));
+    /// assert!(stats.futex_waits() > 0);
+    /// // The worker arrived last.
+    /// assert!(worker.join().unwrap().is_fast_path());
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// - This method requires the `stats` feature.
+    /// - Only the blocking waits of a [`Rendezvous`] record statistics, the
+    ///   other waits return the default ones.
+    pub fn stats(&self) -> WaitStats {
+        self.stats
+    }
+}
+
+thread_local! {
+    /// The number of futex waits made by the current thread.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    static FUTEX_WAITS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Records that the current thread is about to wait on a futex.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn futex_waiting() {
+    FUTEX_WAITS.with(|n| n.set(n.get().wrapping_add(1)));
+}
+
+/// Records the statistics of a blocking wait.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct WaitRecorder {
+    start: Instant,
+    futex_waits: u32,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl WaitRecorder {
+    pub(crate) fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            futex_waits: FUTEX_WAITS.with(Cell::get),
+        }
+    }
+
+    /// Called once the wait returned, `live` being the value returned by the
+    /// arrival of the waiting reference.
+    pub(crate) fn finish(self, live: Count) -> WaitStats {
+        let fast_path = live == 0;
+        WaitStats {
+            blocked: if fast_path {
+                Duration::ZERO
+            } else {
+                self.start.elapsed()
+            },
+            futex_waits: FUTEX_WAITS.with(Cell::get).wrapping_sub(self.futex_waits),
+            fast_path,
+        }
+    }
+}
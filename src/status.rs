@@ -0,0 +1,73 @@
+//! Snapshots of the state of a rendezvous.
+
+use crate::{Count, Observer, RDVInner, Rendezvous};
+
+/// A snapshot of the state of a rendezvous, returned by
+/// [`Rendezvous::status`] and [`Observer::status`].
+///
+/// # Remarks
+///
+/// - The counts are read one after the other, while other references may
+///   be cloned or dropped concurrently: they are only consistent with one
+///   another once the rendezvous is complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RendezvousStatus {
+    /// The number of live references.
+    pub live: Count,
+    /// The number of handles keeping the rendezvous allocated, live or not,
+    /// including observers and weak references.
+    pub total_handles: Count,
+    /// The number of handles which are not live: waiting references,
+    /// observers and weak references.
+    pub waiters: Count,
+    /// The number of threads currently blocked waiting for the live
+    /// references, while the other waiters are still running or waiting
+    /// asynchronously.
+    pub blocked: u32,
+    /// Whether all the references arrived.
+    pub completed: bool,
+}
+
+impl RDVInner {
+    fn status(&self) -> RendezvousStatus {
+        let live = self.counters.live();
+        let total_handles = self.counters.alloc_dep();
+        RendezvousStatus {
+            live,
+            total_handles,
+            waiters: total_handles.saturating_sub(live),
+            blocked: self.counters.parked(),
+            completed: live == 0,
+        }
+    }
+}
+
+impl Rendezvous {
+    /// Returns a snapshot of the state of the rendezvous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let observer = rdv.observer();
+    /// let status = rdv.status();
+    /// assert_eq!((status.live, status.waiters, status.blocked), (1, 1, 0));
+    /// drop(rdv);
+    /// assert!(observer.status().completed);
+    /// ```
+    pub fn status(&self) -> RendezvousStatus {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.status()
+    }
+}
+
+impl Observer {
+    /// Returns a snapshot of the state of the observed rendezvous.
+    pub fn status(&self) -> RendezvousStatus {
+        // Safety: self exist so the ptr is valid
+        unsafe { self.ptr.as_ref() }.status()
+    }
+}
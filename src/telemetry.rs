@@ -0,0 +1,44 @@
+//! Metrics of the `metrics` feature, recorded through the `metrics` facade.
+//!
+//! | Metric                              | Kind      |
+//! |-------------------------------------|-----------|
+//! | `rendezvous_created_total`          | Counter   |
+//! | `rendezvous_waits_total`            | Counter   |
+//! | `rendezvous_futex_wakes_total`      | Counter   |
+//! | `rendezvous_wait_duration_seconds`  | Histogram |
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+use std::time::Instant;
+
+pub(crate) fn created() {
+    metrics::counter!("rendezvous_created_total").increment(1);
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) fn futex_woken() {
+    metrics::counter!("rendezvous_futex_wakes_total").increment(1);
+}
+
+/// Records a blocking wait, and its duration once dropped.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct WaitTimer {
+    start: Instant,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl WaitTimer {
+    pub(crate) fn start() -> Self {
+        metrics::counter!("rendezvous_waits_total").increment(1);
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl Drop for WaitTimer {
+    fn drop(&mut self) {
+        metrics::histogram!("rendezvous_wait_duration_seconds")
+            .record(self.start.elapsed().as_secs_f64());
+    }
+}
@@ -0,0 +1,55 @@
+//! Spans and events of the `tracing` feature.
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+use std::time::Instant;
+
+use crate::{Count, RDVInner};
+
+pub(crate) fn registered(inner: &RDVInner, n: Count) {
+    tracing::trace!(
+        name = &*inner.name,
+        added = n,
+        remaining = inner.counters.live(),
+        "rendezvous cloned"
+    );
+}
+
+pub(crate) fn arrived(inner: &RDVInner, n: Count, live: Count) {
+    tracing::trace!(
+        name = &*inner.name,
+        arrived = n,
+        remaining = live,
+        "rendezvous reference arrived"
+    );
+}
+
+/// The span of a blocking wait, recording how long it waited.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+pub(crate) struct WaitSpan {
+    span: tracing::span::EnteredSpan,
+    start: Instant,
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl WaitSpan {
+    pub(crate) fn enter(inner: &RDVInner) -> Self {
+        let span = tracing::debug_span!(
+            "rendezvous wait",
+            name = &*inner.name,
+            remaining = inner.counters.live(),
+            wait_us = tracing::field::Empty
+        );
+        Self {
+            span: span.entered(),
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+impl Drop for WaitSpan {
+    fn drop(&mut self) {
+        let waited = u64::try_from(self.start.elapsed().as_micros()).unwrap_or(u64::MAX);
+        self.span.record("wait_us", waited);
+    }
+}
@@ -0,0 +1,106 @@
+//! A counter of pending units of work, after Go's `sync.WaitGroup`.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use rendezvous_core::Futex as _;
+
+use crate::futex::Futex;
+
+/// A counter of pending units of work, which are [added](WaitGroup::add)
+/// up front and marked [done](WaitGroup::done) one by one, and whose
+/// waiters are released once there are none left.
+///
+/// Unlike [`Rendezvous`](crate::Rendezvous), units of work are counted
+/// explicitly rather than by handles, so a wait group is typically shared
+/// through a reference or an [`Arc`](std::sync::Arc).
+///
+/// # Examples
+///
+/// ```
+/// use rendezvous::WaitGroup;
+/// use std::thread;
+///
+/// let wg = WaitGroup::new();
+/// thread::scope(|s| {
+///     wg.add(4);
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             // Do some work.
+///             wg.done();
+///         });
+///     }
+///     wg.wait();
+/// });
+/// ```
+pub struct WaitGroup {
+    pending: AtomicU32,
+}
+
+impl WaitGroup {
+    /// Creates a wait group without pending units of work.
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicU32::new(0),
+        }
+    }
+
+    /// Adds `n` pending units of work.
+    ///
+    /// # Panics
+    ///
+    /// If there would be more than 2³² - 1 pending units of work.
+    pub fn add(&self, n: u32) {
+        self.pending
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |p| p.checked_add(n))
+            .expect("There should not be more than 2^32 - 1 pending units of work.");
+    }
+
+    /// Marks a unit of work as done, releasing the waiters if it was the
+    /// last pending one.
+    ///
+    /// # Panics
+    ///
+    /// If there is no pending unit of work.
+    pub fn done(&self) {
+        let prev = self
+            .pending
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |p| p.checked_sub(1))
+            .expect("WaitGroup::done should not be called more times than added.");
+        if prev == 1 {
+            Futex::wake_all(&self.pending);
+        }
+    }
+
+    /// Blocks until there is no pending unit of work.
+    pub fn wait(&self) {
+        loop {
+            let pending = self.pending();
+            if pending == 0 {
+                return;
+            }
+            Futex::wait(&self.pending, pending);
+        }
+    }
+
+    /// Returns the number of pending units of work.
+    pub fn pending(&self) -> u32 {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+impl Default for WaitGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for WaitGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitGroup")
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
@@ -0,0 +1,104 @@
+//! Warnings about blocking waits lasting longer than a threshold, to make
+//! hung shutdowns visible.
+//!
+//! With the `watchdog` feature and once a threshold is
+//! [set](set_threshold), a blocking wait on a rendezvous which lasts longer
+//! than the threshold emits a warning with the name of the rendezvous and
+//! the number of references it still waits for, then keeps waiting. The
+//! warning is a `tracing` event with the `tracing` feature, and is written
+//! to the standard error otherwise.
+//!
+//! # Examples
+//!
+//! ```
+//! use rendezvous::Rendezvous;
+//! use std::{thread, time::Duration};
+//!
+//! rendezvous::watchdog::set_threshold(Some(Duration::from_millis(10)));
+//! let rdv = Rendezvous::named("shutdown");
+//! let worker = rdv.clone();
+//! thread::spawn(move || {
+//!     thread::sleep(Duration::from_millis(50));
+//!     drop(worker);
+//! });
+//! // Warns "rendezvous `shutdown` waited for more than 10ms, 1 remaining".
+//! rdv.wait().unwrap();
+//! ```
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{Count, RDVInner};
+
+/// The threshold in nanoseconds, 0 when disabled.
+static THRESHOLD: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the duration after which a blocking wait emits a warning, or
+/// disables the warnings with `None`, which is the default.
+///
+/// The threshold applies to the waits starting afterwards, on all the
+/// rendezvous of the process. A zero threshold disables the warnings.
+pub fn set_threshold(threshold: Option<Duration>) {
+    let nanos = threshold.map_or(0, |threshold| {
+        u64::try_from(threshold.as_nanos()).unwrap_or(u64::MAX)
+    });
+    THRESHOLD.store(nanos, Ordering::Relaxed);
+}
+
+/// Returns the duration after which a blocking wait emits a warning, if
+/// set.
+pub fn threshold() -> Option<Duration> {
+    match THRESHOLD.load(Ordering::Relaxed) {
+        0 => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+/// The watchdog of a blocking wait, see the hooks module.
+pub(crate) struct Watchdog {
+    // None once warned.
+    threshold: Option<Duration>,
+}
+
+impl Watchdog {
+    pub(crate) fn new() -> Self {
+        Self {
+            threshold: threshold(),
+        }
+    }
+
+    /// Returns when to check the wait next, since its start.
+    pub(crate) fn next_check(&self) -> Option<Duration> {
+        self.threshold
+    }
+
+    /// Checks the wait, `elapsed` since its start, on `inner` which has
+    /// `live` live references.
+    pub(crate) fn check(&mut self, inner: &RDVInner, live: Count, elapsed: Duration) {
+        if let Some(threshold) = self.threshold.filter(|&t| elapsed >= t) {
+            warn(inner, live, threshold);
+            self.threshold = None;
+        }
+    }
+}
+
+#[cold]
+fn warn(inner: &RDVInner, remaining: Count, threshold: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        name = &*inner.name,
+        remaining,
+        threshold_ms = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX),
+        "rendezvous wait exceeded the watchdog threshold"
+    );
+    #[cfg(not(feature = "tracing"))]
+    {
+        let name = match &*inner.name {
+            "" => String::new(),
+            name => format!(" `{name}`"),
+        };
+        eprintln!("rendezvous{name} waited for more than {threshold:?}, {remaining} remaining");
+    }
+}
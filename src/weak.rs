@@ -0,0 +1,97 @@
+//! References which do not keep a rendezvous from completing.
+
+use core::{fmt, ptr::NonNull};
+
+use crate::{release, RDVInner, Rendezvous};
+
+/// A reference to a [`Rendezvous`] which does not take part in it, created
+/// by [`Rendezvous::downgrade`].
+///
+/// It keeps the rendezvous allocated, but not live: it can be upgraded back
+/// to a [`Rendezvous`] only until all the references are dropped.
+pub struct WeakRendezvous {
+    ptr: NonNull<RDVInner>,
+}
+
+impl Rendezvous {
+    /// Returns a [`WeakRendezvous`] to this rendezvous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rendezvous::Rendezvous;
+    ///
+    /// let rdv = Rendezvous::new();
+    /// let weak = rdv.downgrade();
+    /// assert!(weak.upgrade().is_some());
+    /// drop(rdv);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    pub fn downgrade(&self) -> WeakRendezvous {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        WeakRendezvous { ptr: self.ptr }
+    }
+}
+
+impl WeakRendezvous {
+    /// Returns a new reference to the rendezvous, or `None` if all the
+    /// references were dropped already, or if the last one is about to be,
+    /// e.g. while running the closure of
+    /// [`wait_and_then`](Rendezvous::wait_and_then).
+    ///
+    /// # Panics
+    ///
+    /// If there are already 2³² - 1 copies of the rendezvous.
+    #[cfg_attr(feature = "debug-handles", track_caller)]
+    pub fn upgrade(&self) -> Option<Rendezvous> {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        inner.counters.register_retained().then(|| {
+            crate::hooks::registered(inner, 1);
+            Rendezvous::from_ptr(self.ptr)
+        })
+    }
+
+    /// Returns whether all the references to the rendezvous are dropped, in
+    /// which case it cannot be upgraded anymore.
+    pub fn is_complete(&self) -> bool {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.counters.live() == 0
+    }
+}
+
+impl Drop for WeakRendezvous {
+    fn drop(&mut self) {
+        // Safety: self owns an allocation dependency it does not use
+        // afterwards.
+        unsafe { release(self.ptr) };
+    }
+}
+
+impl Clone for WeakRendezvous {
+    fn clone(&self) -> Self {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.retain();
+        Self { ptr: self.ptr }
+    }
+}
+
+// Safety: weak references only touch the counters, which are atomic.
+unsafe impl Send for WeakRendezvous {}
+// Safety: same as above.
+unsafe impl Sync for WeakRendezvous {}
+
+impl fmt::Debug for WeakRendezvous {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Safety: self exist so the ptr is valid
+        let inner = unsafe { self.ptr.as_ref() };
+        f.debug_struct("WeakRendezvous")
+            .field("live barriers", &inner.counters.live())
+            .finish()
+    }
+}
@@ -6,7 +6,7 @@ trait BarrierLike: Clone + Send {
 
 impl BarrierLike for rendezvous::Rendezvous {
     fn wait(self) {
-        rendezvous::Rendezvous::wait(self);
+        let _ = rendezvous::Rendezvous::wait(self);
     }
 }
 
@@ -57,7 +57,7 @@ fn main() {
     for _ in 0..N_ITER {
         let b = rendezvous::Rendezvous::new();
         f("".into(), N_CHILD, DEPTH, b.clone());
-        b.wait();
+        let _ = b.wait();
     }
     let end = start.elapsed();
     eprintln!("rendez-vous: {}ms", end.as_millis());